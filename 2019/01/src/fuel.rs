@@ -0,0 +1,284 @@
+//! Fuel requirements for arbitrarily heavy modules.
+//!
+//! Masses are big integers so inputs far beyond `i64` can be used. The fuel for a module's fuel can
+//! be followed link by link with `fuel_chain`, or summed directly with `total_fuel_closed_form`.
+use std::fmt;
+
+use num_bigint::BigInt;
+
+/// A line of input that isn't a mass.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BadLine {
+    /// The line number, starting from 1.
+    pub line: usize,
+    pub text: String,
+}
+
+/// Every bad line found in an input.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    pub lines: Vec<BadLine>,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} bad line(s)", self.lines.len())?;
+        for bad in &self.lines {
+            write!(f, "\n  line {}: {:?} is not a mass", bad.line, bad.text)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// Reads one mass per line, ignoring blank lines.
+pub fn parse_masses(text: &str) -> Result<Vec<BigInt>, ParseError> {
+    let mut masses = Vec::new();
+    let mut bad = Vec::new();
+    for (i, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        match line.parse() {
+            Ok(mass) => masses.push(mass),
+            Err(_) => bad.push(BadLine {
+                line: i + 1,
+                text: line.into(),
+            }),
+        }
+    }
+    if bad.is_empty() {
+        Ok(masses)
+    } else {
+        Err(ParseError { lines: bad })
+    }
+}
+
+/// The fuel needed to launch `mass`, ignoring the mass of the fuel itself. Small masses need
+/// negative fuel.
+pub fn required_fuel(mass: &BigInt) -> BigInt {
+    mass / 3 - 2
+}
+
+/// The extra fuel needed to carry `fuel`, then to carry that fuel and so on, until no more is
+/// needed.
+pub fn fuel_chain(fuel: &BigInt) -> Vec<BigInt> {
+    let zero = BigInt::default();
+    let mut chain = Vec::new();
+    let mut additional = required_fuel(fuel);
+    while additional > zero {
+        let next = required_fuel(&additional);
+        chain.push(additional);
+        additional = next;
+    }
+    chain
+}
+
+/// The fuel needed to launch `mass` and all of its fuel, without following the chain.
+///
+/// Adding 3 to a mass turns `mass / 3 - 2` into plain division by 3: if `n = mass + 3` then the
+/// `k`th fuel in the chain is `n / 3^k - 3`. The chain stops before the first term below 1, so
+/// with `K` terms the total is `n / 3 + ... + n / 3^K - 3K`. Summing `n / 3^k` over every `k`
+/// gives `(n - digit_sum(n)) / 2`, where the digit sum is taken in base 3, so the first `K` terms
+/// are that sum for `n` minus the same sum for `n / 3^K`.
+pub fn total_fuel_closed_form(mass: &BigInt) -> BigInt {
+    let direct = required_fuel(mass);
+    if direct <= BigInt::default() {
+        return direct;
+    }
+    let n = mass + 3;
+    let mut terms = 0u64;
+    let mut power = BigInt::from(3);
+    while n >= &power * 4 {
+        terms += 1;
+        power *= 3;
+    }
+    let tail = &n / (power / 3);
+    (&n - digit_sum(&n)) / 2 - (&tail - digit_sum(&tail)) / 2 - 3 * terms
+}
+
+/// The sum of the base 3 digits of a non-negative number.
+fn digit_sum(n: &BigInt) -> u64 {
+    n.to_radix_le(3).1.into_iter().map(u64::from).sum()
+}
+
+/// The fuel for one module, split into what the module needs and what its fuel needs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Breakdown {
+    pub mass: BigInt,
+    pub direct: BigInt,
+    /// The fuel for the direct fuel, then for that fuel and so on.
+    pub chain: Vec<BigInt>,
+}
+
+impl Breakdown {
+    pub fn new(mass: BigInt) -> Self {
+        let direct = required_fuel(&mass);
+        let chain = fuel_chain(&direct);
+        Breakdown {
+            mass,
+            direct,
+            chain,
+        }
+    }
+
+    pub fn total(&self) -> BigInt {
+        &self.direct + self.chain.iter().sum::<BigInt>()
+    }
+}
+
+/// A breakdown for every module. Displays as a table with a line per module and the totals.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Report {
+    pub modules: Vec<Breakdown>,
+}
+
+impl Report {
+    pub fn new(masses: &[BigInt]) -> Self {
+        Report {
+            modules: masses.iter().cloned().map(Breakdown::new).collect(),
+        }
+    }
+
+    /// The fuel for just the modules.
+    pub fn direct(&self) -> BigInt {
+        self.modules.iter().map(|module| &module.direct).sum()
+    }
+
+    /// The fuel for the modules and all of their fuel.
+    pub fn total(&self) -> BigInt {
+        self.modules.iter().map(Breakdown::total).sum()
+    }
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(
+            f,
+            "{:>4}  {:>12} {:>12} {:>12}  chain",
+            "#", "mass", "direct", "total"
+        )?;
+        for (i, module) in self.modules.iter().enumerate() {
+            let chain: Vec<String> = module.chain.iter().map(BigInt::to_string).collect();
+            writeln!(
+                f,
+                "{:>4}  {:>12} {:>12} {:>12}  {}",
+                i + 1,
+                module.mass,
+                module.direct,
+                module.total(),
+                chain.join(" + ")
+            )?;
+        }
+        writeln!(
+            f,
+            "{:>4}  {:>12} {:>12} {:>12}",
+            "",
+            "",
+            self.direct(),
+            self.total()
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn big(n: i64) -> BigInt {
+        BigInt::from(n)
+    }
+
+    /// The fuel for `mass` and all of its fuel, following the chain link by link.
+    fn total_fuel(mass: &BigInt) -> BigInt {
+        let direct = required_fuel(mass);
+        let chain: BigInt = fuel_chain(&direct).into_iter().sum();
+        direct + chain
+    }
+
+    #[test]
+    fn test_required_fuel() {
+        assert_eq!(required_fuel(&big(12)), big(2));
+        assert_eq!(required_fuel(&big(14)), big(2));
+        assert_eq!(required_fuel(&big(1969)), big(654));
+        assert_eq!(required_fuel(&big(100756)), big(33583));
+    }
+
+    #[test]
+    fn test_total_fuel() {
+        assert_eq!(total_fuel(&big(12)), big(2));
+        assert_eq!(total_fuel(&big(14)), big(2));
+        assert_eq!(total_fuel(&big(1969)), big(966));
+        assert_eq!(total_fuel(&big(100756)), big(50346));
+    }
+
+    #[test]
+    fn closed_form_matches_chain() {
+        for mass in (-10..5000).chain(100_000..101_000) {
+            assert_eq!(
+                total_fuel_closed_form(&big(mass)),
+                total_fuel(&big(mass)),
+                "mass {}",
+                mass
+            );
+        }
+        let huge: BigInt = "123456789012345678901234567890123456789".parse().unwrap();
+        assert_eq!(total_fuel_closed_form(&huge), total_fuel(&huge));
+    }
+
+    #[test]
+    fn breakdown() {
+        let module = Breakdown::new(big(1969));
+        assert_eq!(module.direct, big(654));
+        assert_eq!(module.chain, vec![big(216), big(70), big(21), big(5)]);
+        assert_eq!(module.total(), big(966));
+
+        let report = Report::new(&[big(14), big(1969)]);
+        assert_eq!(report.direct(), big(656));
+        assert_eq!(report.total(), big(968));
+        assert!(report.to_string().contains("216 + 70 + 21 + 5"));
+    }
+
+    #[test]
+    fn totals_agree() {
+        let masses: Vec<BigInt> = [12, 14, 1969, 100756, 3, 0]
+            .iter()
+            .map(|mass| big(*mass))
+            .chain(parse_masses("98765432109876543210\n").unwrap())
+            .collect();
+        let report = Report::new(&masses);
+        let iterative: BigInt = masses.iter().map(total_fuel).sum();
+        let closed_form: BigInt = masses.iter().map(total_fuel_closed_form).sum();
+        assert_eq!(report.total(), iterative);
+        assert_eq!(report.total(), closed_form);
+    }
+
+    #[test]
+    fn bad_lines() {
+        assert_eq!(
+            parse_masses("12\n\n 1969 \n99999999999999999999999\n"),
+            Ok(vec![
+                big(12),
+                big(1969),
+                "99999999999999999999999".parse().unwrap()
+            ])
+        );
+        assert_eq!(
+            parse_masses("12\nfour\n14\n1.5\n"),
+            Err(ParseError {
+                lines: vec![
+                    BadLine {
+                        line: 2,
+                        text: "four".into()
+                    },
+                    BadLine {
+                        line: 4,
+                        text: "1.5".into()
+                    },
+                ]
+            })
+        );
+    }
+}
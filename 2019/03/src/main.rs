@@ -1,7 +1,6 @@
-use std::collections::{BTreeSet, HashMap};
 use std::convert::TryFrom;
 use std::io::prelude::*;
-use std::{env, error, fs, io, iter, ops};
+use std::{env, error, fs, io, iter};
 
 type Point = (isize, isize);
 type BoxResult<T> = Result<T, Box<dyn error::Error>>;
@@ -43,54 +42,113 @@ impl std::convert::TryFrom<&str> for WireRun {
     }
 }
 
-impl WireRun {
-    fn path_points(&self) -> Vec<Point> {
-        let mut current: Point = (0, 0);
-        let mut points = vec![current];
-        for segment in self.0.iter() {
-            let segment_points: Box<dyn Iterator<Item = Point>> = match *segment {
-                WireDir::Y(0) => continue,
-                WireDir::X(0) => continue,
-                WireDir::Y(distance) if distance > 0 => {
-                    let y_coords = (current.1 + 1)..=(current.1 + distance);
-                    Box::new(iter::repeat(current.0).zip(y_coords))
-                }
-                WireDir::Y(distance) if distance < 0 => {
-                    let y_coords = ((current.1 + distance)..current.1).rev();
-                    Box::new(iter::repeat(current.0).zip(y_coords))
-                }
-                WireDir::X(distance) if distance > 0 => {
-                    let x_coords = (current.0 + 1)..=(current.0 + distance);
-                    Box::new(x_coords.zip(iter::repeat(current.1)))
-                }
-                WireDir::X(distance) if distance < 0 => {
-                    let x_coords = ((current.0 + distance)..current.0).rev();
-                    Box::new(x_coords.zip(iter::repeat(current.1)))
-                }
-                // Compiler will not detect exhaustive match of numeric ranges
-                // https://github.com/rust-lang/rust/issues/12483
-                // The above paterns should be exhaustive
-                WireDir::X(_) => unreachable!(),
-                WireDir::Y(_) => unreachable!(),
-            };
-            let segment_points: Vec<_> = segment_points.collect();
-            points.extend(segment_points);
-            if let Some(point) = points.last() {
-                current = *point
+/// A straight part of a wire. `steps` is the length of wire laid before `start`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Segment {
+    start: Point,
+    end: Point,
+    steps: usize,
+}
+
+impl Segment {
+    fn x_range(&self) -> (isize, isize) {
+        (self.start.0.min(self.end.0), self.start.0.max(self.end.0))
+    }
+
+    fn y_range(&self) -> (isize, isize) {
+        (self.start.1.min(self.end.1), self.start.1.max(self.end.1))
+    }
+
+    /// The length of wire laid to reach a point on this segment.
+    fn steps_to(&self, point: Point) -> usize {
+        self.steps + manhattan_distance(self.start, point) as usize
+    }
+
+    /// The part of the grid covered by both segments. Two crossing segments share a single point
+    /// while two collinear segments can share a run of points.
+    fn overlap(&self, other: &Segment) -> Option<(Point, Point)> {
+        let (ax0, ax1) = self.x_range();
+        let (bx0, bx1) = other.x_range();
+        let (ay0, ay1) = self.y_range();
+        let (by0, by1) = other.y_range();
+        let x = (ax0.max(bx0), ax1.min(bx1));
+        let y = (ay0.max(by0), ay1.min(by1));
+        if x.0 > x.1 || y.0 > y.1 {
+            return None;
+        }
+        Some(((x.0, y.0), (x.1, y.1)))
+    }
+}
+
+/// Points where two wires meet: a single crossing point, or a stretch where they run together.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Crossing {
+    from: Point,
+    to: Point,
+    a: Segment,
+    b: Segment,
+}
+
+impl Crossing {
+    fn contains(&self, point: Point) -> bool {
+        self.from.0 <= point.0
+            && point.0 <= self.to.0
+            && self.from.1 <= point.1
+            && point.1 <= self.to.1
+    }
+
+    /// The points of the crossing that can be the closest to the origin or have the shortest
+    /// combined wire length. Wire length changes linearly along a crossing so it is smallest at
+    /// an end, and distance from the origin is smallest at the point nearest to it. When one of
+    /// those is `exclude` the points next to it are used instead.
+    fn candidates(&self, exclude: Option<Point>) -> Vec<Point> {
+        let nearest = (
+            0.max(self.from.0).min(self.to.0),
+            0.max(self.from.1).min(self.to.1),
+        );
+        let mut points = Vec::new();
+        for point in [self.from, self.to, nearest].iter().copied() {
+            if Some(point) != exclude {
+                points.push(point);
+                continue;
             }
+            let (x, y) = point;
+            let neighbours = [(x - 1, y), (x + 1, y), (x, y - 1), (x, y + 1)];
+            points.extend(neighbours.iter().filter(|pt| self.contains(**pt)));
         }
         points
     }
+}
 
-    fn intersections(&self, other: &WireRun) -> Vec<Point> {
-        let self_pts = self.path_points();
-        let mut other_pts = BTreeSet::new();
-        other_pts.extend(other.path_points());
-        self_pts
-            .iter()
-            .filter(move |pt| other_pts.contains(pt))
-            .map(|pt| *pt)
-            .collect()
+impl WireRun {
+    fn segments(&self) -> Vec<Segment> {
+        let mut start: Point = (0, 0);
+        let mut steps = 0;
+        let mut segments = Vec::with_capacity(self.0.len());
+        for dir in self.0.iter() {
+            let (end, length) = match *dir {
+                WireDir::X(0) | WireDir::Y(0) => continue,
+                WireDir::X(distance) => ((start.0 + distance, start.1), distance.abs()),
+                WireDir::Y(distance) => ((start.0, start.1 + distance), distance.abs()),
+            };
+            segments.push(Segment { start, end, steps });
+            start = end;
+            steps += length as usize;
+        }
+        segments
+    }
+
+    fn intersections(&self, other: &WireRun) -> Vec<Crossing> {
+        let other_segments = other.segments();
+        let mut crossings = Vec::new();
+        for a in self.segments() {
+            for b in other_segments.iter() {
+                if let Some((from, to)) = a.overlap(b) {
+                    crossings.push(Crossing { from, to, a, b: *b });
+                }
+            }
+        }
+        crossings
     }
 
     fn closest_intersection(
@@ -99,16 +157,11 @@ impl WireRun {
         exclude_anchor: bool,
         other: &WireRun,
     ) -> Option<Point> {
-        let mut intersections = self.intersections(other);
-        if exclude_anchor {
-            intersections = intersections
-                .iter()
-                .filter(|pt| **pt != anchor)
-                .map(|pt| *pt)
-                .collect();
-        }
-        intersections.sort_by_key(|pt| manhattan_distance((0, 0), *pt));
-        intersections.first().map(|pt| *pt)
+        let exclude = if exclude_anchor { Some(anchor) } else { None };
+        self.intersections(other)
+            .iter()
+            .flat_map(|crossing| crossing.candidates(exclude))
+            .min_by_key(|pt| manhattan_distance((0, 0), *pt))
     }
 
     fn shortest_intersection(
@@ -116,32 +169,16 @@ impl WireRun {
         exclude_origin: bool,
         other: &WireRun,
     ) -> Option<(usize, Point)> {
-        let mut distances_a: HashMap<Point, usize> = HashMap::new();
-        distances_a.reserve(self.0.len());
-        for (dist, pt) in self.path_points().iter().enumerate() {
-            match distances_a.get(pt) {
-                Some(visited_dist) => {
-                    if dist < *visited_dist {
-                        distances_a.insert(*pt, dist);
-                    }
-                }
-                None => {
-                    distances_a.insert(*pt, dist);
-                }
-            }
-        }
-        let mut intersections: Vec<(usize, Point)> = Vec::new();
-        for (dist_b, pt) in other.path_points().iter().enumerate() {
-            if exclude_origin && *pt == (0, 0) {
-                continue;
-            }
-            match distances_a.get(pt) {
-                Some(dist_a) => intersections.push((dist_a + dist_b, *pt)),
-                None => continue,
-            }
-        }
-        intersections.sort();
-        intersections.first().map(|val| *val)
+        let exclude = if exclude_origin { Some((0, 0)) } else { None };
+        self.intersections(other)
+            .iter()
+            .flat_map(|crossing| {
+                crossing
+                    .candidates(exclude)
+                    .into_iter()
+                    .map(move |pt| (crossing.a.steps_to(pt) + crossing.b.steps_to(pt), pt))
+            })
+            .min()
     }
 }
 
@@ -161,18 +198,196 @@ fn manhattan_distance(a: Point, b: Point) -> isize {
     xdistance + ydistance
 }
 
+/// A place where two of several wires meet: a single point, or a stretch where they run together.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+struct WireIntersection {
+    /// Indexes of the two wires, lowest first.
+    wires: (usize, usize),
+    /// The ends of the shared stretch, which are the same point when the wires just cross.
+    from: Point,
+    to: Point,
+    /// The point of the stretch nearest the origin and its Manhattan distance.
+    closest: Point,
+    distance: isize,
+    /// The point of the stretch with the shortest combined length of both wires, and that length.
+    fewest_steps: Point,
+    steps: usize,
+}
+
+/// Every place where any two of `wires` meet, ordered by wire pair and then position. Each stretch
+/// the wires share gives one intersection, however long it is, and so does each point where they
+/// cross. A point the wires cross more than once is listed once per crossing.
+fn intersections_between(wires: &[WireRun], exclude_origin: bool) -> Vec<WireIntersection> {
+    let exclude = if exclude_origin { Some((0, 0)) } else { None };
+    let mut found = Vec::new();
+    for (i, a) in wires.iter().enumerate() {
+        for (j, b) in wires.iter().enumerate().skip(i + 1) {
+            let crossings = a.intersections(b);
+            for crossing in crossings.iter() {
+                // where a wire turns along a stretch both wires share, the segments either side
+                // of the turn also cross at that one point
+                let within_overlap = crossing.from == crossing.to
+                    && crossings
+                        .iter()
+                        .any(|other| other.from != other.to && other.contains(crossing.from));
+                if within_overlap {
+                    continue;
+                }
+                let candidates = crossing.candidates(exclude);
+                let closest = candidates
+                    .iter()
+                    .map(|pt| (manhattan_distance((0, 0), *pt), *pt))
+                    .min();
+                let fewest_steps = candidates
+                    .iter()
+                    .map(|pt| (crossing.a.steps_to(*pt) + crossing.b.steps_to(*pt), *pt))
+                    .min();
+                if let (Some((distance, closest)), Some((steps, fewest_steps))) =
+                    (closest, fewest_steps)
+                {
+                    found.push(WireIntersection {
+                        wires: (i, j),
+                        from: crossing.from,
+                        to: crossing.to,
+                        closest,
+                        distance,
+                        fewest_steps,
+                        steps,
+                    });
+                }
+            }
+        }
+    }
+    found.sort();
+    found
+}
+
+/// Where an intersection is with its distance and steps, such as `at (3, 3): distance 6, steps
+/// 40`. A shared stretch also says which of its points have them.
+fn describe(intersection: &WireIntersection) -> String {
+    if intersection.from == intersection.to {
+        return format!(
+            "at {:?}: distance {}, steps {}",
+            intersection.from, intersection.distance, intersection.steps
+        );
+    }
+    format!(
+        "from {:?} to {:?}: distance {} at {:?}, steps {} at {:?}",
+        intersection.from,
+        intersection.to,
+        intersection.distance,
+        intersection.closest,
+        intersection.steps,
+        intersection.fewest_steps
+    )
+}
+
+const SVG_COLORS: [&str; 6] = [
+    "#1f77b4", "#ff7f0e", "#2ca02c", "#9467bd", "#8c564b", "#e377c2",
+];
+
+/// Draws the wires as an SVG image with each intersection circled. Up on the grid is up in the
+/// image and the origin is marked with a square.
+fn render_svg(wires: &[WireRun], intersections: &[WireIntersection]) -> String {
+    let segments: Vec<Vec<Segment>> = wires.iter().map(WireRun::segments).collect();
+    let points = segments
+        .iter()
+        .flatten()
+        .flat_map(|segment| vec![segment.start, segment.end])
+        .chain(iter::once((0, 0)));
+    let (mut min_x, mut max_x, mut min_y, mut max_y) = (0, 0, 0, 0);
+    for (x, y) in points {
+        min_x = min_x.min(x);
+        max_x = max_x.max(x);
+        min_y = min_y.min(y);
+        max_y = max_y.max(y);
+    }
+    let size = (max_x - min_x).max(max_y - min_y).max(1);
+    let margin = size / 20 + 1;
+    let marker = size / 150 + 1;
+
+    let view = (
+        min_x - margin,
+        -max_y - margin,
+        max_x - min_x + 2 * margin,
+        max_y - min_y + 2 * margin,
+    );
+    let mut svg = String::new();
+    svg += &format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" viewBox=\"{} {} {} {}\">\n",
+        view.0, view.1, view.2, view.3
+    );
+    svg += &format!(
+        "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"white\"/>\n",
+        view.0, view.1, view.2, view.3
+    );
+    for (i, wire) in segments.iter().enumerate() {
+        let mut path: Vec<String> = vec!["0,0".to_string()];
+        path.extend(wire.iter().map(|s| format!("{},{}", s.end.0, -s.end.1)));
+        svg += &format!(
+            "<polyline points=\"{}\" fill=\"none\" stroke=\"{}\" stroke-width=\"2\" \
+             vector-effect=\"non-scaling-stroke\"/>\n",
+            path.join(" "),
+            SVG_COLORS[i % SVG_COLORS.len()]
+        );
+    }
+    svg += &format!(
+        "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"black\"/>\n",
+        -marker,
+        -marker,
+        2 * marker,
+        2 * marker
+    );
+    for intersection in intersections {
+        let title = format!(
+            "<title>wires {} and {} {}</title>",
+            intersection.wires.0,
+            intersection.wires.1,
+            describe(intersection)
+        );
+        let ((x0, y0), (x1, y1)) = (intersection.from, intersection.to);
+        if intersection.from != intersection.to {
+            svg += &format!(
+                "<line x1=\"{}\" y1=\"{}\" x2=\"{}\" y2=\"{}\" stroke=\"red\" \
+                 stroke-width=\"4\" vector-effect=\"non-scaling-stroke\">{}</line>\n",
+                x0, -y0, x1, -y1, title
+            );
+        }
+        let (x, y) = intersection.closest;
+        svg += &format!(
+            "<circle cx=\"{}\" cy=\"{}\" r=\"{}\" fill=\"none\" stroke=\"red\" \
+             stroke-width=\"2\" vector-effect=\"non-scaling-stroke\">{}</circle>\n",
+            x,
+            -y,
+            marker * 2,
+            title
+        );
+    }
+    svg += "</svg>\n";
+    svg
+}
+
 fn main() {
-    let input_path = env::args().nth(1).expect("input file required");
+    let mut args = env::args().skip(1);
+    let input_path = args.next().expect("input file required");
+    let svg_path = args.next();
     let runs = wire_runs_from_file(&input_path).expect("invalid input file");
-    let (wire1, wire2) = match runs.as_slice() {
-        [wire1, wire2] => (wire1, wire2),
-        _ => {
-            eprintln!("two runs are required in the input file");
-            std::process::exit(1);
-        }
-    };
+    if runs.len() < 2 {
+        eprintln!("at least two runs are required in the input file");
+        std::process::exit(1);
+    }
+    let pairs: Vec<(&WireRun, &WireRun)> = runs
+        .iter()
+        .enumerate()
+        .flat_map(|(i, a)| runs[i + 1..].iter().map(move |b| (a, b)))
+        .collect();
+
     println!("part 1");
-    match wire1.closest_intersection((0, 0), true, &wire2) {
+    let closest = pairs
+        .iter()
+        .filter_map(|(a, b)| a.closest_intersection((0, 0), true, b))
+        .min_by_key(|point| manhattan_distance((0, 0), *point));
+    match closest {
         Some(point) => {
             println!("closest point: {:?}", point);
             println!("distance: {}", manhattan_distance((0, 0), point))
@@ -184,7 +399,11 @@ fn main() {
     }
 
     println!("\npart 2");
-    match wire1.shortest_intersection(true, wire2) {
+    let shortest = pairs
+        .iter()
+        .filter_map(|(a, b)| a.shortest_intersection(true, b))
+        .min();
+    match shortest {
         Some((distance, point)) => {
             println!("shortest_intersection: {:?}", point);
             println!("distance: {}", distance);
@@ -194,6 +413,22 @@ fn main() {
             std::process::exit(1);
         }
     }
+
+    let intersections = intersections_between(&runs, true);
+    println!("\nall intersections");
+    for intersection in intersections.iter() {
+        println!(
+            "wires {} and {} {}",
+            intersection.wires.0,
+            intersection.wires.1,
+            describe(intersection)
+        );
+    }
+
+    if let Some(path) = svg_path {
+        fs::write(&path, render_svg(&runs, &intersections)).expect("unable to write svg file");
+        println!("\nlayout written to {}", path);
+    }
 }
 
 #[cfg(test)]
@@ -206,8 +441,19 @@ mod tests {
         let wire = WireRun::try_from(input).unwrap();
         assert_eq!(wire.0, vec![WireDir::X(-2), WireDir::Y(3)]);
         assert_eq!(
-            wire.path_points(),
-            vec![(0, 0), (-1, 0), (-2, 0), (-2, 1), (-2, 2), (-2, 3)],
+            wire.segments(),
+            vec![
+                Segment {
+                    start: (0, 0),
+                    end: (-2, 0),
+                    steps: 0
+                },
+                Segment {
+                    start: (-2, 0),
+                    end: (-2, 3),
+                    steps: 2
+                },
+            ],
         );
     }
 
@@ -217,18 +463,69 @@ mod tests {
         let wire = WireRun::try_from(input).unwrap();
         assert_eq!(wire.0, vec![WireDir::X(2), WireDir::Y(-3)]);
         assert_eq!(
-            wire.path_points(),
-            vec![(0, 0), (1, 0), (2, 0), (2, -1), (2, -2), (2, -3)],
+            wire.segments(),
+            vec![
+                Segment {
+                    start: (0, 0),
+                    end: (2, 0),
+                    steps: 0
+                },
+                Segment {
+                    start: (2, 0),
+                    end: (2, -3),
+                    steps: 2
+                },
+            ],
         );
     }
 
     #[test]
-    fn test_path_points() {
+    fn test_intersections() {
         let wire1 = WireRun::try_from("R8,U5,L5,D3").unwrap();
         let wire2 = WireRun::try_from("U7,R6,D4,L4").unwrap();
-        let mut intersections = BTreeSet::new();
-        intersections.extend(wire1.intersections(&wire2));
-        println!("{:?}", intersections);
+        let mut intersections: Vec<(Point, Point)> = wire1
+            .intersections(&wire2)
+            .iter()
+            .map(|crossing| (crossing.from, crossing.to))
+            .collect();
+        intersections.sort();
+        assert_eq!(
+            intersections,
+            vec![((0, 0), (0, 0)), ((3, 3), (3, 3)), ((6, 5), (6, 5))]
+        );
+    }
+
+    #[test]
+    fn test_collinear_overlap() {
+        // the wires run together from (2, 0) to (4, 0)
+        let wire1 = WireRun::try_from("R4").unwrap();
+        let wire2 = WireRun::try_from("U1,R2,D1,R3,U1,L1,D2").unwrap();
+        let crossings = wire1.intersections(&wire2);
+        assert!(crossings
+            .iter()
+            .any(|crossing| crossing.from == (2, 0) && crossing.to == (4, 0)));
+        assert_eq!(
+            wire1.closest_intersection((0, 0), true, &wire2),
+            Some((2, 0))
+        );
+        // (2, 0) is 2 steps along wire 1 and 4 along wire 2
+        assert_eq!(wire1.shortest_intersection(true, &wire2), Some((6, (2, 0))));
+    }
+
+    #[test]
+    fn test_shared_start() {
+        // both wires leave the origin to the right, so (1, 0) is the nearest crossing
+        let wire1 = WireRun::try_from("R5,U2").unwrap();
+        let wire2 = WireRun::try_from("R3,D2").unwrap();
+        assert_eq!(
+            wire1.closest_intersection((0, 0), true, &wire2),
+            Some((1, 0))
+        );
+        assert_eq!(wire1.shortest_intersection(true, &wire2), Some((2, (1, 0))));
+        assert_eq!(
+            wire1.shortest_intersection(false, &wire2),
+            Some((0, (0, 0)))
+        );
     }
 
     #[test]
@@ -270,4 +567,57 @@ mod tests {
         let (distance, _point) = wire1.shortest_intersection(true, &wire2).unwrap();
         assert_eq!(distance, 410);
     }
+
+    #[test]
+    fn test_intersections_between() {
+        let wires: Vec<WireRun> = ["R8,U5,L5,D3", "U7,R6,D4,L4", "U3,R8"]
+            .iter()
+            .map(|input| WireRun::try_from(*input).unwrap())
+            .collect();
+        let found: Vec<String> = intersections_between(&wires, true)
+            .iter()
+            .map(|i| format!("{:?} {}", i.wires, describe(i)))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                "(0, 1) at (3, 3): distance 6, steps 40",
+                "(0, 1) at (6, 5): distance 11, steps 30",
+                "(0, 2) at (3, 3): distance 6, steps 26",
+                "(0, 2) at (8, 3): distance 11, steps 22",
+                // wires 1 and 2 leave the origin together and run along y = 3 in opposite
+                // directions
+                "(1, 2) from (0, 0) to (0, 3): distance 1 at (0, 1), steps 2 at (0, 1)",
+                "(1, 2) from (2, 3) to (6, 3): distance 5 at (2, 3), steps 26 at (2, 3)",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_long_overlap() {
+        // the wires share a stretch a million cells long without listing its points
+        let wires: Vec<WireRun> = ["U1,R1000000", "U1,R1000000,U1"]
+            .iter()
+            .map(|input| WireRun::try_from(*input).unwrap())
+            .collect();
+        let found = intersections_between(&wires, true);
+        assert_eq!(found.len(), 2);
+        assert_eq!((found[1].from, found[1].to), ((0, 1), (1_000_000, 1)));
+        assert_eq!((found[1].closest, found[1].distance), ((0, 1), 1));
+        assert_eq!((found[1].fewest_steps, found[1].steps), ((0, 1), 2));
+    }
+
+    #[test]
+    fn test_render_svg() {
+        let wires = vec![
+            WireRun::try_from("R8,U5,L5,D3").unwrap(),
+            WireRun::try_from("U7,R6,D4,L4").unwrap(),
+        ];
+        let intersections = intersections_between(&wires, true);
+        let svg = render_svg(&wires, &intersections);
+        assert!(svg.starts_with("<svg "));
+        assert!(svg.contains("points=\"0,0 8,0 8,-5 3,-5 3,-2\""));
+        assert_eq!(svg.matches("<circle").count(), 2);
+        assert!(svg.contains("wires 0 and 1 at (6, 5): distance 11, steps 30"));
+    }
 }
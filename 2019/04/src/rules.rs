@@ -0,0 +1,371 @@
+//! Counts fixed length digit codes that follow a set of rules.
+//!
+//! A rule reads a code's digits from left to right carrying a small state, and can reject the
+//! code as soon as a digit breaks it. Codes that reach the same position with the same rule states
+//! have the same future, so they are counted together instead of checking every number in a range.
+use std::collections::HashMap;
+
+pub trait Rule {
+    /// The state before any digit has been read.
+    fn start(&self) -> u64;
+    /// The state after reading `digit`, or `None` if the digit breaks the rule.
+    fn next(&self, state: u64, digit: u8) -> Option<u64>;
+    /// Whether a code that ends in `state` follows the rule.
+    fn accept(&self, state: u64) -> bool;
+}
+
+/// Digits never decrease from left to right.
+pub struct NonDecreasing;
+
+impl Rule for NonDecreasing {
+    fn start(&self) -> u64 {
+        0
+    }
+
+    fn next(&self, last: u64, digit: u8) -> Option<u64> {
+        // the state is one more than the last digit so 0 means no digit yet
+        let digit = u64::from(digit) + 1;
+        if digit < last {
+            None
+        } else {
+            Some(digit)
+        }
+    }
+
+    fn accept(&self, _: u64) -> bool {
+        true
+    }
+}
+
+/// At least one run of repeated adjacent digits has a length between `min` and `max`.
+pub struct RunLength {
+    pub min: usize,
+    pub max: usize,
+}
+
+impl RunLength {
+    pub fn at_least(min: usize) -> Self {
+        RunLength {
+            min,
+            max: usize::MAX,
+        }
+    }
+
+    pub fn exactly(len: usize) -> Self {
+        RunLength { min: len, max: len }
+    }
+
+    fn matches(&self, len: u64) -> bool {
+        (self.min as u64..=self.max as u64).contains(&len)
+    }
+}
+
+// The state packs whether a matching run was found in bit 0, the current run length in bits 1-31
+// and one more than the current digit above that.
+const FOUND: u64 = 1;
+const RUN_SHIFT: u64 = 1;
+const RUN_MASK: u64 = (1 << 31) - 1;
+const DIGIT_SHIFT: u64 = 32;
+
+impl Rule for RunLength {
+    fn start(&self) -> u64 {
+        0
+    }
+
+    fn next(&self, state: u64, digit: u8) -> Option<u64> {
+        let mut found = state & FOUND;
+        let mut run = (state >> RUN_SHIFT) & RUN_MASK;
+        let digit = u64::from(digit) + 1;
+        if state >> DIGIT_SHIFT == digit {
+            run += 1;
+        } else {
+            if self.matches(run) {
+                found = FOUND;
+            }
+            run = 1;
+        }
+        Some(found | run << RUN_SHIFT | digit << DIGIT_SHIFT)
+    }
+
+    fn accept(&self, state: u64) -> bool {
+        state & FOUND == FOUND || self.matches((state >> RUN_SHIFT) & RUN_MASK)
+    }
+}
+
+/// Codes of `length` digits in `base`, with leading zeros, that follow every rule in the set.
+pub struct RuleSet {
+    length: usize,
+    base: u8,
+    rules: Vec<Box<dyn Rule>>,
+}
+
+impl RuleSet {
+    pub fn new(length: usize, base: u8) -> Self {
+        assert!(base >= 2, "base must be at least 2");
+        RuleSet {
+            length,
+            base,
+            rules: Vec::new(),
+        }
+    }
+
+    /// Adds a rule that codes must also follow.
+    pub fn with<R: Rule + 'static>(mut self, rule: R) -> Self {
+        self.rules.push(Box::new(rule));
+        self
+    }
+
+    /// The digits of `value`, most significant first, or `None` if it needs more digits than the
+    /// codes have.
+    pub fn digits(&self, mut value: u64) -> Option<Vec<u8>> {
+        let base = u64::from(self.base);
+        let mut digits = vec![0; self.length];
+        for digit in digits.iter_mut().rev() {
+            *digit = (value % base) as u8;
+            value /= base;
+        }
+        if value == 0 {
+            Some(digits)
+        } else {
+            None
+        }
+    }
+
+    fn start(&self) -> Vec<u64> {
+        self.rules.iter().map(|rule| rule.start()).collect()
+    }
+
+    fn next(&self, states: &[u64], digit: u8) -> Option<Vec<u64>> {
+        self.rules
+            .iter()
+            .zip(states)
+            .map(|(rule, state)| rule.next(*state, digit))
+            .collect()
+    }
+
+    fn accept(&self, states: &[u64]) -> bool {
+        self.rules
+            .iter()
+            .zip(states)
+            .all(|(rule, state)| rule.accept(*state))
+    }
+
+    /// The number of valid codes.
+    pub fn count(&self) -> u64 {
+        self.count_from(0, self.start(), &mut HashMap::new())
+    }
+
+    /// The number of valid codes with a value from `min` to `max` inclusive.
+    pub fn count_range(&self, min: u64, max: u64) -> u64 {
+        if min > max {
+            return 0;
+        }
+        let below_min = match min.checked_sub(1) {
+            Some(below) => self.count_to(below),
+            None => 0,
+        };
+        self.count_to(max) - below_min
+    }
+
+    /// The number of valid codes with a value up to `max`.
+    fn count_to(&self, max: u64) -> u64 {
+        match self.digits(max) {
+            Some(bound) => self.count_bounded(0, self.start(), &bound, &mut HashMap::new()),
+            None => self.count(),
+        }
+    }
+
+    /// Counts codes whose first `pos` digits equal `bound` and whose rest are no larger.
+    fn count_bounded(
+        &self,
+        pos: usize,
+        states: Vec<u64>,
+        bound: &[u8],
+        memo: &mut HashMap<(usize, Vec<u64>), u64>,
+    ) -> u64 {
+        if pos == self.length {
+            return self.accept(&states) as u64;
+        }
+        let mut count = 0;
+        for digit in 0..=bound[pos] {
+            if let Some(next) = self.next(&states, digit) {
+                count += if digit == bound[pos] {
+                    self.count_bounded(pos + 1, next, bound, memo)
+                } else {
+                    self.count_from(pos + 1, next, memo)
+                };
+            }
+        }
+        count
+    }
+
+    /// Counts the ways to finish a code from `pos` onward with any digits.
+    fn count_from(
+        &self,
+        pos: usize,
+        states: Vec<u64>,
+        memo: &mut HashMap<(usize, Vec<u64>), u64>,
+    ) -> u64 {
+        if pos == self.length {
+            return self.accept(&states) as u64;
+        }
+        if let Some(count) = memo.get(&(pos, states.clone())) {
+            return *count;
+        }
+        let count = (0..self.base)
+            .filter_map(|digit| self.next(&states, digit))
+            .map(|next| self.count_from(pos + 1, next, memo))
+            .sum();
+        memo.insert((pos, states), count);
+        count
+    }
+
+    /// Up to `limit` valid codes with a value from `min` to `max` inclusive, smallest first.
+    pub fn codes_in_range(&self, min: u64, max: u64, limit: usize) -> Vec<Vec<u8>> {
+        let low = match self.digits(min) {
+            Some(low) => low,
+            None => return vec![],
+        };
+        let high = self
+            .digits(max)
+            .unwrap_or_else(|| vec![self.base - 1; self.length]);
+        let mut codes = Vec::new();
+        let mut prefix = Vec::with_capacity(self.length);
+        self.collect(
+            &mut prefix,
+            self.start(),
+            (&low, &high),
+            (true, true),
+            limit,
+            &mut codes,
+        );
+        codes
+    }
+
+    /// Extends `prefix` depth first in increasing order, staying within the bounds while the
+    /// prefix is still equal to them.
+    fn collect(
+        &self,
+        prefix: &mut Vec<u8>,
+        states: Vec<u64>,
+        bounds: (&[u8], &[u8]),
+        tight: (bool, bool),
+        limit: usize,
+        codes: &mut Vec<Vec<u8>>,
+    ) {
+        let pos = prefix.len();
+        if codes.len() >= limit {
+            return;
+        }
+        if pos == self.length {
+            if self.accept(&states) {
+                codes.push(prefix.clone());
+            }
+            return;
+        }
+        let first = if tight.0 { bounds.0[pos] } else { 0 };
+        let last = if tight.1 {
+            bounds.1[pos]
+        } else {
+            self.base - 1
+        };
+        for digit in first..=last {
+            if let Some(next) = self.next(&states, digit) {
+                prefix.push(digit);
+                let tight = (tight.0 && digit == first, tight.1 && digit == last);
+                self.collect(prefix, next, bounds, tight, limit, codes);
+                prefix.pop();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check(rules: &RuleSet, digits: &[u8]) -> bool {
+        let mut states = rules.start();
+        for digit in digits {
+            states = match rules.next(&states, *digit) {
+                Some(states) => states,
+                None => return false,
+            };
+        }
+        rules.accept(&states)
+    }
+
+    fn brute_force(rules: &RuleSet, min: u64, max: u64) -> u64 {
+        (min..=max)
+            .filter(|value| check(rules, &rules.digits(*value).unwrap()))
+            .count() as u64
+    }
+
+    #[test]
+    fn puzzle_examples() {
+        let part1 = RuleSet::new(6, 10)
+            .with(NonDecreasing)
+            .with(RunLength::at_least(2));
+        assert!(check(&part1, &[1, 1, 1, 1, 1, 1]));
+        assert!(!check(&part1, &[2, 2, 3, 4, 5, 0]));
+        assert!(!check(&part1, &[1, 2, 3, 7, 8, 9]));
+
+        let part2 = RuleSet::new(6, 10)
+            .with(NonDecreasing)
+            .with(RunLength::exactly(2));
+        assert!(check(&part2, &[1, 1, 2, 2, 3, 3]));
+        assert!(!check(&part2, &[1, 2, 3, 4, 4, 4]));
+        assert!(check(&part2, &[1, 1, 1, 1, 2, 2]));
+    }
+
+    #[test]
+    fn counts_match_brute_force() {
+        let rule_sets = vec![
+            RuleSet::new(6, 10)
+                .with(NonDecreasing)
+                .with(RunLength::at_least(2)),
+            RuleSet::new(6, 10)
+                .with(NonDecreasing)
+                .with(RunLength::exactly(2)),
+            RuleSet::new(8, 3).with(RunLength::exactly(3)),
+            RuleSet::new(4, 16),
+        ];
+        for rules in rule_sets {
+            for (min, max) in &[(0, 999), (123_456, 234_567), (3000, 3000), (5, 4)] {
+                let largest = u64::from(rules.base).pow(rules.length as u32) - 1;
+                let max = (*max).min(largest);
+                assert_eq!(
+                    rules.count_range(*min, max),
+                    brute_force(&rules, *min, max),
+                    "{}..={}",
+                    min,
+                    max
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn whole_space() {
+        assert_eq!(RuleSet::new(3, 2).count(), 8);
+        // non-decreasing codes are multisets of digits: C(base + length - 1, length)
+        assert_eq!(RuleSet::new(6, 10).with(NonDecreasing).count(), 5005);
+        assert_eq!(RuleSet::new(2, 10).count_range(0, 1_000), 100);
+    }
+
+    #[test]
+    fn codes_in_range() {
+        let rules = RuleSet::new(6, 10)
+            .with(NonDecreasing)
+            .with(RunLength::exactly(2));
+        assert_eq!(
+            rules.codes_in_range(353_096, 843_212, 3),
+            vec![
+                vec![3, 5, 5, 5, 6, 6],
+                vec![3, 5, 5, 5, 7, 7],
+                vec![3, 5, 5, 5, 8, 8],
+            ]
+        );
+        assert!(rules.codes_in_range(999_999, 999_999, 3).is_empty());
+    }
+}
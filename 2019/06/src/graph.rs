@@ -0,0 +1,367 @@
+//! A general purpose graph with interned node names.
+//!
+//! Nodes are stored in an arena and referred to by `NodeId`, an index into it. Names are only
+//! looked up when adding edges or printing, so traversals never clone or hash strings.
+use std::collections::{HashMap, VecDeque};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct NodeId(usize);
+
+/// Which edges a traversal follows.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    Inbound,
+    Outbound,
+    Any,
+}
+
+#[derive(Debug, Default)]
+pub struct Graph {
+    names: Vec<String>,
+    ids: HashMap<String, NodeId>,
+    outbound: Vec<Vec<NodeId>>,
+    inbound: Vec<Vec<NodeId>>,
+}
+
+impl Graph {
+    pub fn new() -> Self {
+        Graph::default()
+    }
+
+    /// The id of the node called `name`, adding the node if it doesn't exist yet.
+    pub fn intern(&mut self, name: &str) -> NodeId {
+        if let Some(id) = self.ids.get(name) {
+            return *id;
+        }
+        let id = NodeId(self.names.len());
+        self.names.push(name.into());
+        self.ids.insert(name.into(), id);
+        self.outbound.push(Vec::new());
+        self.inbound.push(Vec::new());
+        id
+    }
+
+    pub fn id(&self, name: &str) -> Option<NodeId> {
+        self.ids.get(name).copied()
+    }
+
+    pub fn name(&self, id: NodeId) -> &str {
+        &self.names[id.0]
+    }
+
+    /// The number of nodes.
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+
+    pub fn nodes(&self) -> impl Iterator<Item = NodeId> {
+        (0..self.names.len()).map(NodeId)
+    }
+
+    /// Adds an edge from `from` to `to`, adding either node if needed.
+    pub fn add_edge(&mut self, from: &str, to: &str) -> (NodeId, NodeId) {
+        let from = self.intern(from);
+        let to = self.intern(to);
+        self.outbound[from.0].push(to);
+        self.inbound[to.0].push(from);
+        (from, to)
+    }
+
+    /// Adds edges both ways between `a` and `b`.
+    pub fn add_undirected_edge(&mut self, a: &str, b: &str) -> (NodeId, NodeId) {
+        let (a, b) = self.add_edge(a, b);
+        self.outbound[b.0].push(a);
+        self.inbound[a.0].push(b);
+        (a, b)
+    }
+
+    pub fn count_edges(&self) -> usize {
+        self.outbound.iter().map(Vec::len).sum()
+    }
+
+    /// The nodes one edge away from `id`.
+    pub fn neighbours(
+        &self,
+        id: NodeId,
+        direction: Direction,
+    ) -> impl Iterator<Item = NodeId> + '_ {
+        let (outbound, inbound): (&[NodeId], &[NodeId]) = match direction {
+            Direction::Outbound => (&self.outbound[id.0], &[]),
+            Direction::Inbound => (&[], &self.inbound[id.0]),
+            Direction::Any => (&self.outbound[id.0], &self.inbound[id.0]),
+        };
+        outbound.iter().chain(inbound).copied()
+    }
+
+    /// Visits the nodes reachable from `start` in breadth first order, starting with `start`
+    /// itself. Each node is given with its distance from `start`.
+    pub fn bfs(&self, start: NodeId, direction: Direction) -> Bfs<'_> {
+        let mut visited = vec![false; self.len()];
+        visited[start.0] = true;
+        Bfs {
+            graph: self,
+            queue: VecDeque::from(vec![(start, 0)]),
+            visited,
+            direction,
+        }
+    }
+
+    /// Visits the nodes reachable from `start` in depth first preorder, starting with `start`
+    /// itself.
+    pub fn dfs(&self, start: NodeId, direction: Direction) -> Dfs<'_> {
+        Dfs {
+            graph: self,
+            stack: vec![start],
+            visited: vec![false; self.len()],
+            direction,
+        }
+    }
+
+    /// The nodes on a shortest path from `from` to `to`, including both ends.
+    pub fn shortest_path(
+        &self,
+        from: NodeId,
+        to: NodeId,
+        direction: Direction,
+    ) -> Option<Vec<NodeId>> {
+        let mut previous: Vec<Option<NodeId>> = vec![None; self.len()];
+        let mut queue = VecDeque::from(vec![from]);
+        let mut visited = vec![false; self.len()];
+        visited[from.0] = true;
+        while let Some(id) = queue.pop_front() {
+            if id == to {
+                let mut path = vec![to];
+                while let Some(prev) = previous[path[path.len() - 1].0] {
+                    path.push(prev);
+                }
+                path.reverse();
+                return Some(path);
+            }
+            for next in self.neighbours(id, direction) {
+                if !visited[next.0] {
+                    visited[next.0] = true;
+                    previous[next.0] = Some(id);
+                    queue.push_back(next);
+                }
+            }
+        }
+        None
+    }
+
+    /// The closest node that both `a` and `b` can reach by following `direction` edges, for
+    /// example the nearest shared parent in a tree whose edges point from child to parent. A node
+    /// counts as reaching itself.
+    pub fn lowest_common_ancestor(
+        &self,
+        a: NodeId,
+        b: NodeId,
+        direction: Direction,
+    ) -> Option<NodeId> {
+        let mut from_a: Vec<Option<usize>> = vec![None; self.len()];
+        for (id, depth) in self.bfs(a, direction) {
+            from_a[id.0] = Some(depth);
+        }
+        self.bfs(b, direction)
+            .filter_map(|(id, depth)| from_a[id.0].map(|a_depth| (a_depth + depth, id)))
+            .min()
+            .map(|(_, id)| id)
+    }
+
+    /// Orders the nodes so that every edge goes from an earlier node to a later one. Returns
+    /// `None` if the graph has a cycle.
+    pub fn topological_sort(&self) -> Option<Vec<NodeId>> {
+        let mut remaining: Vec<usize> = self.inbound.iter().map(Vec::len).collect();
+        let mut ready: VecDeque<NodeId> = self.nodes().filter(|id| remaining[id.0] == 0).collect();
+        let mut order = Vec::with_capacity(self.len());
+        while let Some(id) = ready.pop_front() {
+            order.push(id);
+            for next in &self.outbound[id.0] {
+                remaining[next.0] -= 1;
+                if remaining[next.0] == 0 {
+                    ready.push_back(*next);
+                }
+            }
+        }
+        if order.len() == self.len() {
+            Some(order)
+        } else {
+            None
+        }
+    }
+}
+
+pub struct Bfs<'a> {
+    graph: &'a Graph,
+    queue: VecDeque<(NodeId, usize)>,
+    visited: Vec<bool>,
+    direction: Direction,
+}
+
+impl<'a> Iterator for Bfs<'a> {
+    type Item = (NodeId, usize);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (id, depth) = self.queue.pop_front()?;
+        for next in self.graph.neighbours(id, self.direction) {
+            if !self.visited[next.0] {
+                self.visited[next.0] = true;
+                self.queue.push_back((next, depth + 1));
+            }
+        }
+        Some((id, depth))
+    }
+}
+
+pub struct Dfs<'a> {
+    graph: &'a Graph,
+    stack: Vec<NodeId>,
+    visited: Vec<bool>,
+    direction: Direction,
+}
+
+impl<'a> Iterator for Dfs<'a> {
+    type Item = NodeId;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let id = self.stack.pop()?;
+            if self.visited[id.0] {
+                continue;
+            }
+            self.visited[id.0] = true;
+            let start = self.stack.len();
+            let visited = &self.visited;
+            self.stack.extend(
+                self.graph
+                    .neighbours(id, self.direction)
+                    .filter(|next| !visited[next.0]),
+            );
+            // neighbours are visited in the order they were added
+            self.stack[start..].reverse();
+            return Some(id);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(graph: &Graph, ids: impl IntoIterator<Item = NodeId>) -> Vec<&str> {
+        ids.into_iter().map(|id| graph.name(id)).collect()
+    }
+
+    fn diamond() -> Graph {
+        let mut graph = Graph::new();
+        graph.add_edge("a", "b");
+        graph.add_edge("a", "c");
+        graph.add_edge("b", "d");
+        graph.add_edge("c", "d");
+        graph.add_edge("d", "e");
+        graph
+    }
+
+    #[test]
+    fn interning() {
+        let mut graph = diamond();
+        assert_eq!(graph.len(), 5);
+        let d = graph.id("d").unwrap();
+        assert_eq!(graph.intern("d"), d);
+        assert_eq!(graph.name(d), "d");
+        assert_eq!(graph.id("z"), None);
+        assert_eq!(graph.count_edges(), 5);
+    }
+
+    #[test]
+    fn traversal_order() {
+        let graph = diamond();
+        let a = graph.id("a").unwrap();
+        let bfs: Vec<(&str, usize)> = graph
+            .bfs(a, Direction::Outbound)
+            .map(|(id, depth)| (graph.name(id), depth))
+            .collect();
+        assert_eq!(bfs, vec![("a", 0), ("b", 1), ("c", 1), ("d", 2), ("e", 3)]);
+        assert_eq!(
+            names(&graph, graph.dfs(a, Direction::Outbound)),
+            vec!["a", "b", "d", "e", "c"]
+        );
+        let e = graph.id("e").unwrap();
+        assert_eq!(
+            names(&graph, graph.dfs(e, Direction::Inbound)),
+            vec!["e", "d", "b", "a", "c"]
+        );
+    }
+
+    #[test]
+    fn paths_and_ancestors() {
+        let graph = diamond();
+        let id = |name| graph.id(name).unwrap();
+        let path = graph
+            .shortest_path(id("b"), id("c"), Direction::Any)
+            .unwrap();
+        // outbound edges are followed first, so the path goes through d rather than a
+        assert_eq!(names(&graph, path), vec!["b", "d", "c"]);
+        assert_eq!(
+            graph.shortest_path(id("b"), id("c"), Direction::Outbound),
+            None
+        );
+        assert_eq!(
+            graph.lowest_common_ancestor(id("b"), id("c"), Direction::Outbound),
+            Some(id("d"))
+        );
+        assert_eq!(
+            graph.lowest_common_ancestor(id("b"), id("c"), Direction::Inbound),
+            Some(id("a"))
+        );
+    }
+
+    #[test]
+    fn topological_sort() {
+        let mut graph = diamond();
+        let order = names(&graph, graph.topological_sort().unwrap());
+        assert_eq!(order, vec!["a", "b", "c", "d", "e"]);
+        graph.add_edge("e", "a");
+        assert_eq!(graph.topological_sort(), None);
+    }
+
+    #[test]
+    fn orbit_tree() {
+        // edges point from an object to the one it orbits
+        let mut graph = Graph::new();
+        graph.add_edge("B", "COM");
+        graph.add_edge("C", "B");
+        graph.add_edge("D", "B");
+        graph.add_edge("E", "D");
+        let id = |name| graph.id(name).unwrap();
+        assert_eq!(graph.dfs(id("COM"), Direction::Inbound).count(), 5);
+        assert_eq!(
+            names(&graph, graph.dfs(id("D"), Direction::Any)),
+            vec!["D", "B", "COM", "C", "E"]
+        );
+        assert_eq!(
+            graph.lowest_common_ancestor(id("C"), id("E"), Direction::Outbound),
+            Some(id("B"))
+        );
+        assert_eq!(
+            graph.lowest_common_ancestor(id("E"), id("D"), Direction::Outbound),
+            Some(id("D"))
+        );
+        let order = graph.topological_sort().unwrap();
+        assert_eq!(order.last(), Some(&id("COM")));
+    }
+
+    #[test]
+    fn undirected_edges() {
+        let mut graph = Graph::new();
+        graph.add_undirected_edge("x", "y");
+        let y = graph.id("y").unwrap();
+        assert_eq!(
+            names(&graph, graph.neighbours(y, Direction::Outbound)),
+            vec!["x"]
+        );
+    }
+}
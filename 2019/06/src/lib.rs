@@ -0,0 +1,3 @@
+//! The graph behind the day 6 orbit map. It's a library so every traversal it offers is part of
+//! its interface, not just the ones the puzzle uses.
+pub mod graph;
@@ -0,0 +1,340 @@
+//! Builds Intcode program images from a small assembly language.
+//!
+//! Each line holds an optional label, an instruction or `.data` directive, and an optional
+//! comment starting with `;`:
+//!
+//! ```text
+//! start:  in [n]            ; read n
+//!         jf [n], #done
+//!         out [n]
+//!         add [n], #-1, [n]
+//!         jt #1, #start
+//! done:   hlt
+//! n:      .data 0
+//! ```
+//!
+//! Parameters are written `[x]` for position mode, `#x` for immediate mode and `rb+x` or `rb-x`
+//! for relative mode. A value may be a number, a label or a label with an offset (`n+1`).
+//! Mnemonics are case insensitive so the output of the disassembler, including its `0000:`
+//! address prefixes, can be assembled again.
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+
+use crate::operation::Mode;
+use crate::program::Program;
+
+/// Opcode and parameter count for each mnemonic. Names match `Operation::mnemonic`.
+const INSTRUCTIONS: [(&str, i64, usize); 10] = [
+    ("add", 1, 3),
+    ("mul", 2, 3),
+    ("in", 3, 1),
+    ("out", 4, 1),
+    ("jt", 5, 2),
+    ("jf", 6, 2),
+    ("lt", 7, 3),
+    ("eq", 8, 3),
+    ("arb", 9, 1),
+    ("hlt", 99, 0),
+];
+
+/// Instructions whose last parameter is written to and so can't be immediate.
+const WRITES_LAST: [&str; 5] = ["add", "mul", "in", "lt", "eq"];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AssembleError {
+    /// The 1-based source line the error was found on.
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for AssembleError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+impl Error for AssembleError {}
+
+/// A parsed source line with its label and address prefix removed.
+#[derive(Debug)]
+struct Statement<'a> {
+    line: usize,
+    address: usize,
+    mnemonic: &'a str,
+    operands: Vec<&'a str>,
+}
+
+/// Assembles source text into a program image.
+pub fn assemble(source: &str) -> Result<Program, AssembleError> {
+    let mut labels: HashMap<&str, i64> = HashMap::new();
+    let mut statements = Vec::new();
+    let mut address = 0;
+
+    for (i, text) in source.lines().enumerate() {
+        let line = i + 1;
+        let err = |message: String| AssembleError { line, message };
+        let mut text = text.split(';').next().unwrap_or("").trim();
+
+        while let Some((name, rest)) = split_label(text) {
+            if name.chars().all(|c| c.is_ascii_digit()) {
+                let expected: usize = name
+                    .parse()
+                    .map_err(|_| err(format!("bad address {}", name)))?;
+                if expected != address {
+                    return Err(err(format!(
+                        "address prefix {} does not match address {}",
+                        name, address
+                    )));
+                }
+            } else if labels.insert(name, address as i64).is_some() {
+                return Err(err(format!("duplicate label {}", name)));
+            }
+            text = rest;
+        }
+        if text.is_empty() {
+            continue;
+        }
+
+        let (mnemonic, rest) = match text.find(char::is_whitespace) {
+            Some(i) => (&text[..i], text[i..].trim()),
+            None => (text, ""),
+        };
+        let operands: Vec<&str> = if rest.is_empty() {
+            vec![]
+        } else {
+            rest.split(',').map(str::trim).collect()
+        };
+        let size = if mnemonic == ".data" {
+            operands.len()
+        } else {
+            let (_, _, params) =
+                lookup(mnemonic).ok_or_else(|| err(format!("unknown instruction {}", mnemonic)))?;
+            if operands.len() != params {
+                return Err(err(format!(
+                    "{} takes {} parameters, found {}",
+                    mnemonic,
+                    params,
+                    operands.len()
+                )));
+            }
+            params + 1
+        };
+        statements.push(Statement {
+            line,
+            address,
+            mnemonic,
+            operands,
+        });
+        address += size;
+    }
+
+    let mut program = Vec::with_capacity(address);
+    for statement in statements {
+        let err = |message: String| AssembleError {
+            line: statement.line,
+            message,
+        };
+        debug_assert_eq!(program.len(), statement.address);
+        if statement.mnemonic == ".data" {
+            for operand in &statement.operands {
+                program.push(value(operand, &labels).map_err(err)?);
+            }
+            continue;
+        }
+
+        let (name, opcode, _) = lookup(statement.mnemonic).expect("checked in the first pass");
+        let mut code = opcode;
+        let mut params = Vec::new();
+        for (i, operand) in statement.operands.iter().enumerate() {
+            let (val, mode) = parameter(operand, &labels).map_err(err)?;
+            if mode == Mode::Immeadiate
+                && i == statement.operands.len() - 1
+                && WRITES_LAST.contains(&name)
+            {
+                return Err(err(format!("{} can't write to an immediate", name)));
+            }
+            let mode_digit = match mode {
+                Mode::Positional => 0,
+                Mode::Immeadiate => 1,
+                Mode::Relative => 2,
+            };
+            code += mode_digit * 10i64.pow(i as u32 + 2);
+            params.push(val);
+        }
+        program.push(code);
+        program.extend(params);
+    }
+    Ok(program)
+}
+
+fn lookup(mnemonic: &str) -> Option<(&'static str, i64, usize)> {
+    let mnemonic = mnemonic.to_ascii_lowercase();
+    INSTRUCTIONS
+        .iter()
+        .find(|(name, _, _)| *name == mnemonic)
+        .copied()
+}
+
+/// Splits `name: rest` into the label and the rest of the line.
+fn split_label(text: &str) -> Option<(&str, &str)> {
+    let colon = text.find(':')?;
+    let name = text[..colon].trim();
+    let is_name = !name.is_empty() && name.chars().all(|c| c.is_alphanumeric() || c == '_');
+    if is_name {
+        Some((name, text[colon + 1..].trim()))
+    } else {
+        None
+    }
+}
+
+fn parameter(operand: &str, labels: &HashMap<&str, i64>) -> Result<(i64, Mode), String> {
+    if let Some(inner) = operand.strip_prefix('[').and_then(|o| o.strip_suffix(']')) {
+        Ok((value(inner.trim(), labels)?, Mode::Positional))
+    } else if let Some(inner) = operand.strip_prefix('#') {
+        Ok((value(inner.trim(), labels)?, Mode::Immeadiate))
+    } else if let Some(offset) = operand.strip_prefix("rb") {
+        let offset = offset.trim();
+        let val = match offset.strip_prefix('+') {
+            Some(offset) => value(offset.trim(), labels)?,
+            None if offset.starts_with('-') => value(offset, labels)?,
+            None if offset.is_empty() => 0,
+            None => return Err(format!("bad relative parameter {}", operand)),
+        };
+        Ok((val, Mode::Relative))
+    } else {
+        Err(format!(
+            "parameter {} needs a mode: [x], #x or rb+x",
+            operand
+        ))
+    }
+}
+
+/// Resolves a number, label or `label+offset` to a value.
+fn value(text: &str, labels: &HashMap<&str, i64>) -> Result<i64, String> {
+    if let Ok(val) = text.parse() {
+        return Ok(val);
+    }
+    let (name, offset) = match text.find(['+', '-']) {
+        Some(i) if i > 0 => {
+            let offset: i64 = text[i..]
+                .replace('+', "")
+                .trim()
+                .parse()
+                .map_err(|_| format!("bad offset in {}", text))?;
+            (text[..i].trim(), offset)
+        }
+        _ => (text, 0),
+    };
+    labels
+        .get(name)
+        .map(|address| address + offset)
+        .ok_or_else(|| format!("unknown label {}", name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::disassemble::disassemble;
+
+    #[test]
+    fn instructions_and_modes() {
+        let program = assemble(
+            "
+            in [9]
+            eq [9], #8, rb+9
+            out rb-1
+            hlt
+            ",
+        )
+        .unwrap();
+        assert_eq!(program, vec![3, 9, 21008, 9, 8, 9, 204, -1, 99]);
+    }
+
+    #[test]
+    fn labels_and_data() {
+        let program = assemble(
+            "
+            start:  in [n]            ; read n
+                    jf [n], #done
+                    out [n]
+                    add [n], #-1, [n]
+                    jt #1, #start
+            done:   HLT
+            n:      .data 0, n+1, start
+            ",
+        )
+        .unwrap();
+        assert_eq!(
+            program,
+            vec![3, 15, 1006, 15, 14, 4, 15, 1001, 15, -1, 15, 1105, 1, 0, 99, 0, 16, 0]
+        );
+    }
+
+    #[test]
+    fn packet_forwarder() {
+        // the packet network test's program, with its data moved from 100 to just after the code
+        let program = assemble(
+            "
+                    in [address]
+                    jt [address], #receive
+                    out #1
+                    out #42
+                    out #7
+            receive:
+                    in [x]
+                    eq [x], #-1, [idle]
+                    jt [idle], #receive
+                    in [y]
+                    out #255
+                    out [x]
+                    out [y]
+                    jt #1, #receive
+            address: .data 0
+            x:      .data 0
+            y:      .data 0
+            idle:   .data 0
+            ",
+        )
+        .unwrap();
+        assert_eq!(
+            program,
+            vec![
+                3, 31, 1005, 31, 11, 104, 1, 104, 42, 104, 7, 3, 32, 1008, 32, -1, 34, 1005, 34,
+                11, 3, 33, 104, 255, 4, 32, 4, 33, 1105, 1, 11, 0, 0, 0, 0
+            ]
+        );
+    }
+
+    #[test]
+    fn disassembly_round_trip() {
+        let program = vec![
+            3, 21, 1008, 21, 8, 20, 1005, 20, 22, 107, 8, 21, 20, 1006, 20, 31, 1106, 0, 36, 98, 0,
+            0, 1002, 21, 125, 20, 4, 20, 1105, 1, 46, 104, 999, 1105, 1, 46, 1101, 1000, 1, 20, 4,
+            20, 1105, 1, 46, 98, 99,
+        ];
+        let source = disassemble(&program).to_string();
+        assert_eq!(assemble(&source).unwrap(), program);
+    }
+
+    #[test]
+    fn errors() {
+        let err = |source: &str| assemble(source).unwrap_err();
+        assert_eq!(err("hlt\nfoo [1]").line, 2);
+        assert_eq!(
+            err("add [1], [2]").message,
+            "add takes 3 parameters, found 2"
+        );
+        assert_eq!(err("in #1").message, "in can't write to an immediate");
+        assert_eq!(err("out [missing]").message, "unknown label missing");
+        assert_eq!(
+            err("out 4").message,
+            "parameter 4 needs a mode: [x], #x or rb+x"
+        );
+        assert_eq!(err("a: hlt\na: hlt").message, "duplicate label a");
+        assert_eq!(
+            err("0000: hlt\n0002: hlt").message,
+            "address prefix 0002 does not match address 1"
+        );
+    }
+}
@@ -0,0 +1,18 @@
+use std::{env, io};
+
+use intcode::io::{AsciiInput, AsciiOutput};
+use intcode::{load_program_file, Intcomp, State};
+
+fn main() {
+    let path = env::args().nth(1).expect("program file required");
+    let program = load_program_file(&path);
+    let stdin = io::stdin();
+    let input = AsciiInput::new(stdin.lock());
+    let output = AsciiOutput::new(io::stdout());
+    let mut comp = Intcomp::new_with_io(input, output, program);
+    match comp.run() {
+        Ok(State::NeedsInput) => eprintln!("program is waiting for input but stdin is closed"),
+        Ok(_) => {}
+        Err(err) => eprintln!("error: {}", err),
+    }
+}
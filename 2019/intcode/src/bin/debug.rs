@@ -0,0 +1,16 @@
+use std::{env, io};
+
+use intcode::debugger::Debugger;
+use intcode::{load_program_file, Intcomp};
+
+fn main() {
+    let mut args = env::args().skip(1);
+    let path = args.next().expect("program file required");
+    let mut comp = Intcomp::new(load_program_file(&path));
+    comp.input
+        .extend(args.map(|a| a.parse::<i64>().expect("inputs must be numbers")));
+    let stdin = io::stdin();
+    Debugger::new(comp)
+        .repl(stdin.lock(), io::stdout())
+        .expect("unable to read commands");
+}
@@ -0,0 +1,13 @@
+use std::env;
+
+use intcode::disassemble::disassemble_with_entries;
+use intcode::load_program_file;
+
+fn main() {
+    let mut args = env::args().skip(1);
+    let path = args.next().expect("program file required");
+    let mut entries = vec![0];
+    entries.extend(args.map(|a| a.parse::<usize>().expect("entry points must be addresses")));
+    let program = load_program_file(&path);
+    print!("{}", disassemble_with_entries(&program, &entries));
+}
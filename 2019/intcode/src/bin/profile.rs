@@ -0,0 +1,21 @@
+use std::env;
+
+use intcode::{load_program_file, Intcomp, State};
+
+fn main() {
+    let mut args = env::args().skip(1);
+    let path = args.next().expect("program file required");
+    let program = load_program_file(&path);
+    let mut comp = Intcomp::new(program.clone());
+    comp.input
+        .extend(args.map(|a| a.parse::<i64>().expect("inputs must be numbers")));
+    comp.enable_profiling();
+    match comp.run() {
+        Ok(State::NeedsInput) => println!("stopped waiting for input"),
+        Ok(_) => {}
+        Err(err) => println!("error: {}", err),
+    }
+    println!("output: {:?}\n", comp.output);
+    let profile = comp.profile.expect("profiling was enabled");
+    print!("{}", profile.report(&program));
+}
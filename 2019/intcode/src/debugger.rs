@@ -0,0 +1,487 @@
+//! An interactive debugger for Intcode programs.
+//!
+//! The debugger is driven by text commands, one per line, so it can be used from a terminal or
+//! scripted in tests. Run `help` for the list of commands.
+//!
+//! Every executed instruction is kept in a log of the last few instructions along with the
+//! memory cell it overwrote. `back` uses the log to undo instructions one at a time. Undoing an
+//! instruction restores memory and registers but values already read from the input or written
+//! to the output are not given back.
+use std::collections::{BTreeSet, VecDeque};
+use std::convert::TryFrom;
+use std::io::{self, BufRead, Write};
+
+use crate::error::IntcodeError;
+use crate::intcomp::State;
+use crate::network::Machine;
+use crate::operation::Operation;
+
+/// The number of instructions kept in the log by default.
+const DEFAULT_HISTORY: usize = 1000;
+
+/// The most memory cells or instructions a single `mem` or `dis` command lists.
+const MAX_LISTING: usize = 4096;
+
+const HELP: &str = "\
+step [n]          s  execute n instructions (default 1)
+continue          c  run until a breakpoint, watchpoint, halt or missing input
+break <addr>      b  stop before executing the instruction at addr
+watch <addr>      w  stop after an instruction writes to addr
+delete <addr>        remove the breakpoint and watchpoint at addr
+regs              r  show the registers and the next instruction
+mem <addr> [n]    m  show n memory cells starting at addr (default 8, up to 4096)
+set <addr> <val>     write a value to memory
+dis [addr] [n]    d  disassemble n instructions from addr (default the pointer, 5; up to 4096)
+input <val>...    i  queue input values
+output            o  show and clear the output queue
+log [n]           l  show the last n executed instructions (default 10)
+back [n]             undo the last n instructions
+quit              q  stop debugging
+";
+
+/// An executed instruction and the memory cell it overwrote.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Trace {
+    pub pointer: usize,
+    pub relative_base: i64,
+    pub operation: Operation,
+    /// The address written to with its value before and after the instruction.
+    pub write: Option<(usize, i64, i64)>,
+}
+
+/// Why the debugger stopped running the program.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stop {
+    /// Stopped normally, with the computer in this state.
+    State(State),
+    /// The next instruction is at a breakpoint.
+    Breakpoint(usize),
+    /// The last instruction wrote to a watched address.
+    Watchpoint(usize),
+}
+
+#[derive(Debug)]
+pub struct Debugger {
+    pub comp: Machine,
+    breakpoints: BTreeSet<usize>,
+    watchpoints: BTreeSet<usize>,
+    history: VecDeque<Trace>,
+    history_len: usize,
+}
+
+impl Debugger {
+    pub fn new(comp: Machine) -> Self {
+        Debugger {
+            comp,
+            breakpoints: BTreeSet::new(),
+            watchpoints: BTreeSet::new(),
+            history: VecDeque::new(),
+            history_len: DEFAULT_HISTORY,
+        }
+    }
+
+    /// Sets how many executed instructions are kept for `log` and `back`.
+    pub fn with_history(mut self, len: usize) -> Self {
+        self.history_len = len;
+        self
+    }
+
+    pub fn add_breakpoint(&mut self, address: usize) {
+        self.breakpoints.insert(address);
+    }
+
+    pub fn add_watchpoint(&mut self, address: usize) {
+        self.watchpoints.insert(address);
+    }
+
+    /// The logged instructions, oldest first.
+    pub fn history(&self) -> impl Iterator<Item = &Trace> {
+        self.history.iter()
+    }
+
+    /// Executes one instruction and records it in the log.
+    pub fn step(&mut self) -> Result<Stop, IntcodeError> {
+        let comp = &mut self.comp;
+        let pointer = comp.pointer;
+        let relative_base = comp.relative_base;
+        let operation = comp.get_operation(pointer)?;
+        let write = match operation.write_parameter() {
+            Some((val, mode)) => Some(comp.address(val, mode)?),
+            None => None,
+        };
+        let before = write.map(|address| comp.memory.get(address));
+        let state = comp.perform(operation)?;
+        if let State::NeedsInput | State::Halted = state {
+            return Ok(Stop::State(state));
+        }
+
+        let write = write.map(|address| (address, before.unwrap_or(0), comp.memory.get(address)));
+        if self.history.len() == self.history_len {
+            self.history.pop_front();
+        }
+        if self.history_len > 0 {
+            self.history.push_back(Trace {
+                pointer,
+                relative_base,
+                operation,
+                write,
+            });
+        }
+        match write {
+            Some((address, _, _)) if self.watchpoints.contains(&address) => {
+                Ok(Stop::Watchpoint(address))
+            }
+            _ => Ok(Stop::State(state)),
+        }
+    }
+
+    /// Runs until a breakpoint or watchpoint is hit, or the program halts or needs input. An
+    /// instruction at a breakpoint is executed if the debugger is already stopped on it.
+    pub fn resume(&mut self) -> Result<Stop, IntcodeError> {
+        let mut first = true;
+        loop {
+            if !first && self.breakpoints.contains(&self.comp.pointer) {
+                return Ok(Stop::Breakpoint(self.comp.pointer));
+            }
+            first = false;
+            match self.step()? {
+                Stop::State(State::Running)
+                | Stop::State(State::Input(_))
+                | Stop::State(State::Output(_)) => continue,
+                stop => return Ok(stop),
+            }
+        }
+    }
+
+    /// Undoes the last logged instruction. Returns false when the log is empty.
+    pub fn back(&mut self) -> bool {
+        match self.history.pop_back() {
+            Some(trace) => {
+                self.comp.pointer = trace.pointer;
+                self.comp.relative_base = trace.relative_base;
+                if let Some((address, before, _)) = trace.write {
+                    self.comp.memory.set(address, before);
+                }
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Reads commands from `commands` until `quit` or the end of the input, writing replies to
+    /// `out`.
+    pub fn repl<R: BufRead, W: Write>(&mut self, commands: R, mut out: W) -> io::Result<()> {
+        write!(out, "(icdb) ")?;
+        out.flush()?;
+        for line in commands.lines() {
+            if !self.execute(&line?, &mut out)? {
+                break;
+            }
+            write!(out, "(icdb) ")?;
+            out.flush()?;
+        }
+        writeln!(out)
+    }
+
+    /// Runs a single command. Returns false when the command asks to quit.
+    pub fn execute<W: Write>(&mut self, line: &str, out: &mut W) -> io::Result<bool> {
+        let mut words = line.split_whitespace();
+        let command = match words.next() {
+            Some(command) => command,
+            None => return Ok(true),
+        };
+        let args: Vec<&str> = words.collect();
+        let numbers: Result<Vec<i64>, _> = args.iter().map(|a| a.parse::<i64>()).collect();
+        let numbers = match numbers {
+            Ok(numbers) => numbers,
+            Err(_) => {
+                writeln!(out, "arguments must be numbers")?;
+                return Ok(true);
+            }
+        };
+        // addresses and counts can't be negative, but `input` and the value given to `set` can
+        let unsigned = match command {
+            "input" | "i" => 0,
+            "set" => 1,
+            _ => numbers.len(),
+        };
+        let mut counts = Vec::with_capacity(unsigned);
+        for n in &numbers[..unsigned.min(numbers.len())] {
+            match usize::try_from(*n) {
+                Ok(n) => counts.push(n),
+                Err(_) => {
+                    writeln!(out, "bad argument {}", n)?;
+                    return Ok(true);
+                }
+            }
+        }
+        let arg = |i: usize, default: usize| counts.get(i).copied().unwrap_or(default);
+
+        match (command, numbers.len()) {
+            ("step", _) | ("s", _) => {
+                for _ in 0..arg(0, 1) {
+                    self.report_instruction(self.comp.pointer, out)?;
+                    let stop = self.step();
+                    match stop {
+                        Ok(Stop::State(State::Running))
+                        | Ok(Stop::State(State::Input(_)))
+                        | Ok(Stop::State(State::Output(_))) => {}
+                        stop => {
+                            self.report(stop, out)?;
+                            break;
+                        }
+                    }
+                }
+            }
+            ("continue", 0) | ("c", 0) => {
+                let stop = self.resume();
+                self.report(stop, out)?;
+            }
+            ("break", 1) | ("b", 1) => {
+                self.add_breakpoint(arg(0, 0));
+                writeln!(out, "breakpoint at {:04}", arg(0, 0))?;
+            }
+            ("watch", 1) | ("w", 1) => {
+                self.add_watchpoint(arg(0, 0));
+                writeln!(out, "watchpoint at {:04}", arg(0, 0))?;
+            }
+            ("delete", 1) => {
+                self.breakpoints.remove(&arg(0, 0));
+                self.watchpoints.remove(&arg(0, 0));
+            }
+            ("regs", 0) | ("r", 0) => {
+                writeln!(
+                    out,
+                    "pc={} rb={} input={:?} output={:?}",
+                    self.comp.pointer, self.comp.relative_base, self.comp.input, self.comp.output
+                )?;
+                self.report_instruction(self.comp.pointer, out)?;
+            }
+            ("mem", 1) | ("mem", 2) | ("m", 1) | ("m", 2) => {
+                let start = arg(0, 0);
+                let end = match start.checked_add(arg(1, 8)) {
+                    Some(end) if arg(1, 8) <= MAX_LISTING => end,
+                    _ => {
+                        writeln!(out, "bad argument {}", arg(1, 8))?;
+                        return Ok(true);
+                    }
+                };
+                let values: Vec<String> = (start..end)
+                    .map(|address| self.comp.memory.get(address).to_string())
+                    .collect();
+                writeln!(out, "{:04}: {}", start, values.join(", "))?;
+            }
+            ("set", 2) => {
+                self.comp.memory.set(arg(0, 0), numbers[1]);
+            }
+            ("dis", n) | ("d", n) if n <= 2 => {
+                if arg(1, 5) > MAX_LISTING {
+                    writeln!(out, "bad argument {}", arg(1, 5))?;
+                    return Ok(true);
+                }
+                let mut address = arg(0, self.comp.pointer);
+                for _ in 0..arg(1, 5) {
+                    let size = self.report_instruction(address, out)?;
+                    address = match address.checked_add(size) {
+                        Some(next) => next,
+                        None => break,
+                    };
+                }
+            }
+            ("input", _) | ("i", _) => {
+                self.comp.input.extend(&numbers);
+            }
+            ("output", 0) | ("o", 0) => {
+                let values: Vec<String> =
+                    self.comp.output.drain(..).map(|v| v.to_string()).collect();
+                writeln!(out, "{}", values.join(", "))?;
+            }
+            ("log", 0) | ("log", 1) | ("l", 0) | ("l", 1) => {
+                let skip = self.history.len().saturating_sub(arg(0, 10));
+                for trace in self.history.iter().skip(skip) {
+                    write!(out, "{:04}: {}", trace.pointer, trace.operation)?;
+                    if let Some((address, before, after)) = trace.write {
+                        write!(out, "  ; [{}] {} -> {}", address, before, after)?;
+                    }
+                    writeln!(out)?;
+                }
+            }
+            ("back", 0) | ("back", 1) => {
+                for _ in 0..arg(0, 1) {
+                    if !self.back() {
+                        writeln!(out, "no more history")?;
+                        break;
+                    }
+                }
+                self.report_instruction(self.comp.pointer, out)?;
+            }
+            ("quit", 0) | ("q", 0) => return Ok(false),
+            ("help", _) | ("h", _) => write!(out, "{}", HELP)?,
+            _ => writeln!(out, "unknown command {}, try help", line.trim())?,
+        }
+        Ok(true)
+    }
+
+    /// Writes the instruction at `address`, returning its size.
+    fn report_instruction<W: Write>(&self, address: usize, out: &mut W) -> io::Result<usize> {
+        let marker = if self.breakpoints.contains(&address) {
+            '*'
+        } else {
+            ' '
+        };
+        match self.comp.get_operation(address) {
+            Ok(operation) => {
+                writeln!(out, "{}{:04}: {}", marker, address, operation)?;
+                Ok(operation.size())
+            }
+            Err(_) => {
+                let val = self.comp.memory.get(address);
+                writeln!(out, "{}{:04}: .data {}", marker, address, val)?;
+                Ok(1)
+            }
+        }
+    }
+
+    fn report<W: Write>(&self, stop: Result<Stop, IntcodeError>, out: &mut W) -> io::Result<()> {
+        match stop {
+            Ok(Stop::State(State::Halted)) => writeln!(out, "halted"),
+            Ok(Stop::State(State::NeedsInput)) => writeln!(out, "waiting for input"),
+            Ok(Stop::State(_)) => Ok(()),
+            Ok(Stop::Breakpoint(address)) => writeln!(out, "breakpoint at {:04}", address),
+            Ok(Stop::Watchpoint(address)) => {
+                let val = self.comp.memory.get(address);
+                writeln!(out, "watchpoint at {:04}: {}", address, val)
+            }
+            Err(err) => writeln!(out, "error: {}", err),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assemble::assemble;
+    use crate::intcomp::Intcomp;
+
+    fn countdown() -> Debugger {
+        let program = assemble(
+            "
+                    in [n]
+            loop:   jf [n], #done
+                    out [n]
+                    add [n], #-1, [n]
+                    jt #1, #loop
+            done:   hlt
+            n:      .data 0
+            ",
+        )
+        .unwrap();
+        Debugger::new(Intcomp::new(program))
+    }
+
+    fn run(debugger: &mut Debugger, commands: &str) -> String {
+        let mut out = Vec::new();
+        for line in commands.lines() {
+            debugger.execute(line, &mut out).unwrap();
+        }
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn step_and_regs() {
+        let mut debugger = countdown();
+        assert_eq!(
+            run(&mut debugger, "step"),
+            " 0000: IN [15]\nwaiting for input\n"
+        );
+        assert_eq!(
+            run(&mut debugger, "input 2\ns 2\nr"),
+            " 0000: IN [15]\n 0002: JF [15], #14\npc=5 rb=0 input=[] output=[]\n 0005: OUT [15]\n"
+        );
+    }
+
+    #[test]
+    fn breakpoints() {
+        let mut debugger = countdown();
+        debugger.comp.input.push_back(3);
+        assert_eq!(
+            run(&mut debugger, "b 5\nc"),
+            "breakpoint at 0005\nbreakpoint at 0005\n"
+        );
+        assert_eq!(run(&mut debugger, "c\no"), "breakpoint at 0005\n3\n");
+        assert_eq!(
+            run(&mut debugger, "delete 5\nc\nm 15 1"),
+            "halted\n0015: 0\n"
+        );
+        assert_eq!(debugger.comp.output, vec![2, 1]);
+    }
+
+    #[test]
+    fn watchpoints() {
+        let mut debugger = countdown();
+        debugger.comp.input.push_back(2);
+        assert_eq!(
+            run(&mut debugger, "w 15\nc\nc"),
+            "watchpoint at 0015\nwatchpoint at 0015: 2\nwatchpoint at 0015: 1\n"
+        );
+    }
+
+    #[test]
+    fn log_and_back() {
+        let mut debugger = countdown().with_history(3);
+        debugger.comp.input.push_back(2);
+        run(&mut debugger, "s 5");
+        assert_eq!(debugger.history().count(), 3);
+        assert_eq!(
+            run(&mut debugger, "log 2"),
+            "0007: ADD [15], #-1, [15]  ; [15] 2 -> 1\n0011: JT #1, #2\n"
+        );
+        assert_eq!(run(&mut debugger, "back 2"), " 0007: ADD [15], #-1, [15]\n");
+        assert_eq!(debugger.comp.memory[15], 2);
+        assert_eq!(
+            run(&mut debugger, "back 2"),
+            "no more history\n 0005: OUT [15]\n"
+        );
+    }
+
+    #[test]
+    fn bad_arguments() {
+        let mut debugger = countdown();
+        assert_eq!(run(&mut debugger, "mem 0 -1"), "bad argument -1\n");
+        assert_eq!(run(&mut debugger, "mem -3"), "bad argument -3\n");
+        assert_eq!(
+            run(&mut debugger, &format!("mem {} 2", i64::MAX)),
+            format!("{}: 0, 0\n", i64::MAX)
+        );
+        assert_eq!(run(&mut debugger, "step -1"), "bad argument -1\n");
+        assert_eq!(debugger.comp.pointer, 0);
+        assert_eq!(run(&mut debugger, "set 15 -4\nm 15 1"), "0015: -4\n");
+        assert_eq!(run(&mut debugger, "set -15 4"), "bad argument -15\n");
+        assert_eq!(
+            run(&mut debugger, "mem 0 1000000000000"),
+            "bad argument 1000000000000\n"
+        );
+        assert_eq!(run(&mut debugger, "dis 0 5000"), "bad argument 5000\n");
+        assert_eq!(
+            run(&mut debugger, &format!("mem 0 {}", MAX_LISTING))
+                .matches(", ")
+                .count(),
+            MAX_LISTING - 1
+        );
+    }
+
+    #[test]
+    fn repl() {
+        let mut debugger = countdown();
+        let mut out = Vec::new();
+        debugger
+            .repl(&b"dis 0 2\nbogus\nq\ns\n"[..], &mut out)
+            .unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "(icdb)  0000: IN [15]\n 0002: JF [15], #14\n\
+             (icdb) unknown command bogus, try help\n(icdb) \n"
+        );
+        assert_eq!(debugger.comp.pointer, 0);
+    }
+}
@@ -0,0 +1,252 @@
+//! Turns an Intcode program image back into readable instructions.
+//!
+//! Code is found by following the program's control flow from address 0: every instruction falls
+//! through to the next one and jumps to an immediate address are followed. Anything that is never
+//! reached is shown as `.data`. Code only reachable through a computed jump (a jump whose target
+//! is read from memory) can be included by passing its address as an extra entry point.
+use std::convert::TryFrom;
+use std::fmt;
+
+use crate::operation::{Mode, Operation};
+
+/// Values per line when printing a data region.
+const DATA_PER_LINE: usize = 8;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Line {
+    Instruction {
+        address: usize,
+        operation: Operation,
+    },
+    Data {
+        address: usize,
+        values: Vec<i64>,
+    },
+}
+
+impl Line {
+    pub fn address(&self) -> usize {
+        match self {
+            Line::Instruction { address, .. } | Line::Data { address, .. } => *address,
+        }
+    }
+}
+
+impl fmt::Display for Line {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Line::Instruction { address, operation } => {
+                write!(f, "{:04}: {}", address, operation)?;
+                if is_computed_jump(operation) {
+                    write!(f, "  ; computed jump")?;
+                }
+                Ok(())
+            }
+            Line::Data { address, values } => {
+                let values: Vec<String> = values.iter().map(|v| v.to_string()).collect();
+                write!(f, "{:04}: .data {}", address, values.join(", "))
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Disassembly {
+    pub lines: Vec<Line>,
+}
+
+impl Disassembly {
+    /// The instructions that were found, in address order.
+    pub fn instructions(&self) -> impl Iterator<Item = (usize, &Operation)> {
+        self.lines.iter().filter_map(|line| match line {
+            Line::Instruction { address, operation } => Some((*address, operation)),
+            Line::Data { .. } => None,
+        })
+    }
+}
+
+impl fmt::Display for Disassembly {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for line in &self.lines {
+            writeln!(f, "{}", line)?;
+        }
+        Ok(())
+    }
+}
+
+/// Disassembles a program starting from address 0.
+pub fn disassemble(program: &[i64]) -> Disassembly {
+    disassemble_with_entries(program, &[0])
+}
+
+/// Disassembles a program, following control flow from each of the entry addresses.
+pub fn disassemble_with_entries(program: &[i64], entries: &[usize]) -> Disassembly {
+    // the address of the instruction that covers each cell, if any
+    let mut owner: Vec<Option<usize>> = vec![None; program.len()];
+    let mut pending: Vec<usize> = entries.iter().rev().copied().collect();
+
+    while let Some(address) = pending.pop() {
+        if owner.get(address) != Some(&None) {
+            continue;
+        }
+        let operation = match decode(program, address) {
+            Some(operation) => operation,
+            None => continue,
+        };
+        let span = address..address + operation.size();
+        if owner[span.clone()].iter().any(Option::is_some) {
+            continue;
+        }
+        for cell in &mut owner[span.clone()] {
+            *cell = Some(address);
+        }
+        pending.extend(successors(&operation, span.end).into_iter().rev());
+    }
+
+    let mut lines = Vec::new();
+    let mut address = 0;
+    while address < program.len() {
+        if owner[address] == Some(address) {
+            let operation = decode(program, address).expect("instruction was decoded before");
+            let size = operation.size();
+            lines.push(Line::Instruction { address, operation });
+            address += size;
+        } else {
+            let start = address;
+            while address < program.len()
+                && owner[address].is_none()
+                && address - start < DATA_PER_LINE
+            {
+                address += 1;
+            }
+            lines.push(Line::Data {
+                address: start,
+                values: program[start..address].to_vec(),
+            });
+        }
+    }
+    Disassembly { lines }
+}
+
+/// Decodes the instruction at `address`, rejecting values that only decode by accident such as
+/// unknown mode digits, too many modes or an immediate write target.
+pub fn decode(program: &[i64], address: usize) -> Option<Operation> {
+    let code = *program.get(address)?;
+    if code <= 0 {
+        return None;
+    }
+    let size = Operation::size_by_code(code).ok()?;
+    let (_, modes) = Operation::parse_operation_modes(code);
+    if modes.len() >= size || modes.iter().any(|mode| *mode > 2) {
+        return None;
+    }
+    let operation = Operation::try_from(program.get(address..address + size)?).ok()?;
+    if let Some((_, Mode::Immeadiate)) = operation.write_parameter() {
+        return None;
+    }
+    Some(operation)
+}
+
+/// The addresses control can move to after `operation`, which ends at `next`.
+fn successors(operation: &Operation, next: usize) -> Vec<usize> {
+    let (check, check_mode, target, target_mode, jump_if) = match *operation {
+        Operation::Exit => return vec![],
+        Operation::JumpIfTrue {
+            check,
+            check_mode,
+            address,
+            address_mode,
+        } => (check, check_mode, address, address_mode, true),
+        Operation::JumpIfFalse {
+            check,
+            check_mode,
+            address,
+            address_mode,
+        } => (check, check_mode, address, address_mode, false),
+        _ => return vec![next],
+    };
+    let mut successors = Vec::new();
+    // an immediate check is a constant, so the jump is either always or never taken
+    let always = check_mode == Mode::Immeadiate && (check != 0) == jump_if;
+    let never = check_mode == Mode::Immeadiate && (check != 0) != jump_if;
+    if !never && target_mode == Mode::Immeadiate && target >= 0 {
+        successors.push(target as usize);
+    }
+    if !always {
+        successors.push(next);
+    }
+    successors
+}
+
+fn is_computed_jump(operation: &Operation) -> bool {
+    match operation {
+        Operation::JumpIfTrue { address_mode, .. }
+        | Operation::JumpIfFalse { address_mode, .. } => *address_mode != Mode::Immeadiate,
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn straight_line() {
+        let program = [1, 9, 10, 3, 2, 3, 11, 0, 99, 30, 40, 50];
+        assert_eq!(
+            disassemble(&program).to_string(),
+            "0000: ADD [9], [10], [3]\n\
+             0004: MUL [3], [11], [0]\n\
+             0008: HLT\n\
+             0009: .data 30, 40, 50\n"
+        );
+    }
+
+    #[test]
+    fn follows_jumps() {
+        let program = [3, 12, 6, 12, 15, 1, 13, 14, 13, 4, 13, 99, -1, 0, 1, 9];
+        let disassembly = disassemble(&program);
+        let addresses: Vec<usize> = disassembly.instructions().map(|(a, _)| a).collect();
+        assert_eq!(addresses, vec![0, 2, 5, 9, 11]);
+        // the jump target is read from memory so the jump is flagged
+        assert_eq!(
+            disassembly.lines[1].to_string(),
+            "0002: JF [12], [15]  ; computed jump"
+        );
+    }
+
+    #[test]
+    fn unconditional_jump_skips_data() {
+        // jump over a data cell that would otherwise decode as an add
+        let program = [1105, 1, 4, 1, 204, -1, 99];
+        assert_eq!(
+            disassemble(&program).to_string(),
+            "0000: JT #1, #4\n\
+             0003: .data 1\n\
+             0004: OUT rb-1\n\
+             0006: HLT\n"
+        );
+    }
+
+    #[test]
+    fn extra_entries() {
+        let program = [1106, 0, 5, 104, 1, 99];
+        assert_eq!(disassemble(&program).instructions().count(), 2);
+        assert_eq!(
+            disassemble_with_entries(&program, &[0, 3])
+                .instructions()
+                .count(),
+            3
+        );
+    }
+
+    #[test]
+    fn rejects_bad_instructions() {
+        assert_eq!(decode(&[1101, 1, 1, 0], 0).map(|op| op.size()), Some(4));
+        assert_eq!(decode(&[11101, 1, 1, 0], 0), None);
+        assert_eq!(decode(&[301, 1, 1, 0], 0), None);
+        assert_eq!(decode(&[1099], 0), None);
+        assert_eq!(decode(&[1, 1, 1], 0), None);
+        assert_eq!(decode(&[-1], 0), None);
+    }
+}
@@ -0,0 +1,64 @@
+use std::error::Error;
+use std::fmt;
+use std::io;
+
+/// A fault raised while running an Intcode program. Each fault carries the address of the
+/// instruction that caused it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IntcodeError {
+    /// The value at the instruction pointer is not a known instruction.
+    InvalidOpcode { pc: usize, code: i64 },
+    /// The instruction pointer moved outside of the program's memory.
+    OutOfBounds { pc: usize, addr: usize },
+    /// A parameter resolved to an address below 0.
+    NegativeAddress { pc: usize, addr: i64 },
+    /// An instruction tried to write to an immediate mode parameter.
+    ImmediateWrite { pc: usize },
+    /// The program needs more input but none is left.
+    InputExhausted { pc: usize },
+    /// The output sink refused a value.
+    OutputFailed { pc: usize, kind: io::ErrorKind },
+    /// An addition, multiplication or relative base adjustment went past the range of `i64`.
+    Overflow { pc: usize },
+}
+
+impl IntcodeError {
+    /// The address of the instruction that faulted.
+    pub fn pc(&self) -> usize {
+        match *self {
+            IntcodeError::InvalidOpcode { pc, .. }
+            | IntcodeError::OutOfBounds { pc, .. }
+            | IntcodeError::NegativeAddress { pc, .. }
+            | IntcodeError::ImmediateWrite { pc }
+            | IntcodeError::InputExhausted { pc }
+            | IntcodeError::OutputFailed { pc, .. }
+            | IntcodeError::Overflow { pc } => pc,
+        }
+    }
+}
+
+impl fmt::Display for IntcodeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            IntcodeError::InvalidOpcode { pc, code } => {
+                write!(f, "invalid opcode {} at {}", code, pc)
+            }
+            IntcodeError::OutOfBounds { pc, addr } => {
+                write!(f, "address out of bounds: {} at {}", addr, pc)
+            }
+            IntcodeError::NegativeAddress { pc, addr } => {
+                write!(f, "negative address: {} at {}", addr, pc)
+            }
+            IntcodeError::ImmediateWrite { pc } => {
+                write!(f, "write to an immediate parameter at {}", pc)
+            }
+            IntcodeError::InputExhausted { pc } => write!(f, "input exhausted at {}", pc),
+            IntcodeError::OutputFailed { pc, kind } => {
+                write!(f, "output failed at {}: {:?}", pc, kind)
+            }
+            IntcodeError::Overflow { pc } => write!(f, "arithmetic overflow at {}", pc),
+        }
+    }
+}
+
+impl Error for IntcodeError {}
@@ -0,0 +1,295 @@
+//! Runs Intcode programs that draw on a 2D grid.
+//!
+//! A `Canvas` is a sparse grid of values that can be rendered as text. `PaintingRobot` and
+//! `Arcade` couple a computer to a canvas using the two protocols the puzzles use: a robot that
+//! reads the color under it and answers with a color to paint and a direction to turn, and a
+//! screen fed with `(x, y, tile)` triples. Screen coordinates are used, so `y` grows downward.
+use std::collections::HashMap;
+use std::ops::RangeInclusive;
+
+use crate::error::IntcodeError;
+use crate::intcomp::{Intcomp, State};
+use crate::network::Machine;
+use crate::program::Program;
+
+pub type Point = (i64, i64);
+
+/// A sparse grid of values. Cells that were never set read as 0.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Canvas {
+    cells: HashMap<Point, i64>,
+}
+
+impl Canvas {
+    pub fn new() -> Self {
+        Canvas::default()
+    }
+
+    pub fn get(&self, point: Point) -> i64 {
+        self.cells.get(&point).copied().unwrap_or(0)
+    }
+
+    pub fn set(&mut self, point: Point, val: i64) {
+        self.cells.insert(point, val);
+    }
+
+    /// The number of cells that have been set, even if they were set to 0.
+    pub fn len(&self) -> usize {
+        self.cells.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+
+    /// The points holding `val`, in no particular order.
+    pub fn find(&self, val: i64) -> impl Iterator<Item = Point> + '_ {
+        self.cells
+            .iter()
+            .filter(move |(_, v)| **v == val)
+            .map(|(point, _)| *point)
+    }
+
+    /// The x and y ranges covering every cell that has been set.
+    pub fn bounds(&self) -> Option<(RangeInclusive<i64>, RangeInclusive<i64>)> {
+        let xs = self.cells.keys().map(|(x, _)| *x);
+        let ys = self.cells.keys().map(|(_, y)| *y);
+        Some((xs.clone().min()?..=xs.max()?, ys.clone().min()?..=ys.max()?))
+    }
+
+    /// Draws the set area of the canvas as lines of text, using `draw` to pick the character for
+    /// each value.
+    pub fn render<F: Fn(i64) -> char>(&self, draw: F) -> String {
+        let (xs, ys) = match self.bounds() {
+            Some(bounds) => bounds,
+            None => return String::new(),
+        };
+        let mut text = String::new();
+        for y in ys {
+            for x in xs.clone() {
+                text.push(draw(self.get((x, y))));
+            }
+            text.push('\n');
+        }
+        text
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    Up,
+    Right,
+    Down,
+    Left,
+}
+
+impl Direction {
+    pub fn turn_left(self) -> Self {
+        match self {
+            Direction::Up => Direction::Left,
+            Direction::Left => Direction::Down,
+            Direction::Down => Direction::Right,
+            Direction::Right => Direction::Up,
+        }
+    }
+
+    pub fn turn_right(self) -> Self {
+        match self {
+            Direction::Up => Direction::Right,
+            Direction::Right => Direction::Down,
+            Direction::Down => Direction::Left,
+            Direction::Left => Direction::Up,
+        }
+    }
+
+    /// The point one step from `point` in this direction.
+    pub fn step(self, (x, y): Point) -> Point {
+        match self {
+            Direction::Up => (x, y - 1),
+            Direction::Right => (x + 1, y),
+            Direction::Down => (x, y + 1),
+            Direction::Left => (x - 1, y),
+        }
+    }
+}
+
+/// A robot that paints the panel under it. The program is given the color of the current panel
+/// and answers with the color to paint it, then 0 to turn left or 1 to turn right before moving
+/// forward one panel.
+#[derive(Debug, Clone)]
+pub struct PaintingRobot {
+    pub comp: Machine,
+    pub canvas: Canvas,
+    pub position: Point,
+    pub facing: Direction,
+}
+
+impl PaintingRobot {
+    /// Creates a robot at the origin, facing up, on an unpainted canvas.
+    pub fn new(program: Program) -> Self {
+        PaintingRobot {
+            comp: Intcomp::new(program),
+            canvas: Canvas::new(),
+            position: (0, 0),
+            facing: Direction::Up,
+        }
+    }
+
+    /// Runs the robot until its program halts.
+    pub fn run(&mut self) -> Result<(), IntcodeError> {
+        loop {
+            self.comp.input.push_back(self.canvas.get(self.position));
+            let state = self.comp.run()?;
+            while self.comp.output.len() >= 2 {
+                let color = self.comp.output.pop_front().unwrap_or_default();
+                let turn = self.comp.output.pop_front().unwrap_or_default();
+                self.canvas.set(self.position, color);
+                self.facing = match turn {
+                    0 => self.facing.turn_left(),
+                    _ => self.facing.turn_right(),
+                };
+                self.position = self.facing.step(self.position);
+            }
+            if state == State::Halted {
+                return Ok(());
+            }
+        }
+    }
+}
+
+/// An arcade cabinet. The program draws the screen with `(x, y, tile)` triples, except that a
+/// triple starting `(-1, 0)` sets the score. When the program asks for input it is given the
+/// joystick position: -1 for left, 0 for neutral or 1 for right.
+#[derive(Debug, Clone)]
+pub struct Arcade {
+    pub comp: Machine,
+    pub screen: Canvas,
+    pub score: i64,
+}
+
+impl Arcade {
+    pub const EMPTY: i64 = 0;
+    pub const WALL: i64 = 1;
+    pub const BLOCK: i64 = 2;
+    pub const PADDLE: i64 = 3;
+    pub const BALL: i64 = 4;
+
+    pub fn new(program: Program) -> Self {
+        Arcade {
+            comp: Intcomp::new(program),
+            screen: Canvas::new(),
+            score: 0,
+        }
+    }
+
+    /// Runs the game until it halts. `joystick` is asked for the joystick position each time the
+    /// program wants input, after the screen has been updated.
+    pub fn run<F: FnMut(&Arcade) -> i64>(&mut self, mut joystick: F) -> Result<(), IntcodeError> {
+        loop {
+            let state = self.comp.run()?;
+            self.draw();
+            match state {
+                State::NeedsInput => {
+                    let position = joystick(self);
+                    self.comp.input.push_back(position);
+                }
+                _ => return Ok(()),
+            }
+        }
+    }
+
+    /// The position of the first tile of a kind, such as the ball or paddle.
+    pub fn find(&self, tile: i64) -> Option<Point> {
+        self.screen.find(tile).next()
+    }
+
+    /// Draws the screen with `#` for walls, `=` for blocks, `_` for the paddle and `o` for the
+    /// ball.
+    pub fn render(&self) -> String {
+        self.screen.render(|tile| match tile {
+            Arcade::WALL => '#',
+            Arcade::BLOCK => '=',
+            Arcade::PADDLE => '_',
+            Arcade::BALL => 'o',
+            _ => ' ',
+        })
+    }
+
+    fn draw(&mut self) {
+        while self.comp.output.len() >= 3 {
+            let triple: Vec<i64> = self.comp.output.drain(..3).collect();
+            match triple[..] {
+                [-1, 0, score] => self.score = score,
+                [x, y, tile] => self.screen.set((x, y), tile),
+                _ => unreachable!("drained three values"),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assemble::assemble;
+
+    /// A program that reads an input before writing each pair of outputs.
+    fn answers(pairs: &[(i64, i64)]) -> Program {
+        let mut source = String::new();
+        for (a, b) in pairs {
+            source += &format!("in [scratch]\nout #{}\nout #{}\n", a, b);
+        }
+        source += "hlt\nscratch: .data 0\n";
+        assemble(&source).unwrap()
+    }
+
+    #[test]
+    fn painting_robot() {
+        let program = answers(&[(1, 0), (0, 0), (1, 0), (1, 0), (0, 1), (1, 0), (1, 0)]);
+        let mut robot = PaintingRobot::new(program);
+        robot.run().unwrap();
+        assert_eq!(robot.canvas.len(), 6);
+        assert_eq!(robot.position, (0, -1));
+        assert_eq!(robot.facing, Direction::Left);
+        assert_eq!(
+            robot
+                .canvas
+                .render(|color| if color == 1 { '#' } else { '.' }),
+            "..#\n..#\n##.\n"
+        );
+    }
+
+    #[test]
+    fn arcade() {
+        let program = assemble(
+            "
+                    out #1
+                    out #2
+                    out #3
+                    out #6
+                    out #5
+                    out #4
+                    in [joystick]
+                    out #-1
+                    out #0
+                    out [joystick]
+                    hlt
+            joystick: .data 0
+            ",
+        )
+        .unwrap();
+        let mut arcade = Arcade::new(program);
+        let mut seen = None;
+        arcade
+            .run(|arcade| {
+                seen = arcade.find(Arcade::BALL);
+                let (paddle, _) = arcade.find(Arcade::PADDLE).unwrap();
+                let (ball, _) = arcade.find(Arcade::BALL).unwrap();
+                (ball - paddle).signum() * 12345
+            })
+            .unwrap();
+        assert_eq!(seen, Some((6, 5)));
+        assert_eq!(arcade.score, 12345);
+        assert_eq!(arcade.find(Arcade::PADDLE), Some((1, 2)));
+        assert_eq!(arcade.render().lines().nth(3), Some("     o"));
+    }
+}
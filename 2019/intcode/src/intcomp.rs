@@ -0,0 +1,749 @@
+use std::collections::VecDeque;
+use std::convert::TryFrom;
+
+use crate::error::IntcodeError;
+use crate::io::{Input, Output};
+use crate::memory::Memory;
+use crate::operation::{Mode, Operation, MAX_OPERATION_SIZE};
+use crate::profile::{Event, Profile};
+use crate::program::Program;
+
+/// The state of the computer after an operation, or why it stopped running.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum State {
+    /// The last operation completed and the computer can keep going.
+    Running,
+    /// The last operation read this value from the input.
+    Input(i64),
+    /// The last operation wrote this value to the output.
+    Output(i64),
+    /// The computer is paused on an input instruction with no input available. Running again
+    /// retries the instruction.
+    NeedsInput,
+    /// The program reached its exit instruction.
+    Halted,
+}
+
+#[derive(Debug, Clone)]
+pub struct Intcomp<I, O> {
+    pub memory: Memory,
+    pub pointer: usize,
+    /// Base address used by parameters in relative mode. Adjusted by opcode 9.
+    pub relative_base: i64,
+    pub input: I,
+    pub output: O,
+    /// Execution counts, collected by `step` once profiling is enabled.
+    pub profile: Option<Profile>,
+}
+
+/// A saved copy of a computer's memory, registers and I/O, taken with `Intcomp::snapshot`.
+///
+/// Restoring a snapshot rewinds a computer to the point it was taken, so a search can fork a
+/// machine at a decision point rather than replaying the program from the start.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Snapshot<I, O> {
+    memory: Memory,
+    pointer: usize,
+    relative_base: i64,
+    input: I,
+    output: O,
+}
+
+impl<I, O> Snapshot<I, O> {
+    pub fn pointer(&self) -> usize {
+        self.pointer
+    }
+
+    pub fn memory(&self) -> &Memory {
+        &self.memory
+    }
+}
+
+impl<I, O> From<Snapshot<I, O>> for Intcomp<I, O> {
+    fn from(snapshot: Snapshot<I, O>) -> Self {
+        Intcomp {
+            memory: snapshot.memory,
+            pointer: snapshot.pointer,
+            relative_base: snapshot.relative_base,
+            input: snapshot.input,
+            output: snapshot.output,
+            profile: None,
+        }
+    }
+}
+
+impl<I: Clone, O: Clone> Intcomp<I, O> {
+    /// Copies the full state of the computer, including any queued input and output.
+    pub fn snapshot(&self) -> Snapshot<I, O> {
+        Snapshot {
+            memory: self.memory.clone(),
+            pointer: self.pointer,
+            relative_base: self.relative_base,
+            input: self.input.clone(),
+            output: self.output.clone(),
+        }
+    }
+
+    /// Rewinds the computer to the state saved in `snapshot`. The snapshot can be restored again.
+    /// A profile keeps the counts from before the restore.
+    pub fn restore(&mut self, snapshot: &Snapshot<I, O>) {
+        let profile = self.profile.take();
+        *self = Intcomp::from(snapshot.clone());
+        self.profile = profile;
+    }
+}
+
+impl Intcomp<VecDeque<i64>, VecDeque<i64>> {
+    /// Initializes a new integer computer with queues for input and output.
+    pub fn new(program: Program) -> Self {
+        Intcomp::new_with_io(VecDeque::new(), VecDeque::new(), program)
+    }
+}
+
+impl<I: Input, O: Output> Intcomp<I, O> {
+    /// Initializes a new integer computer reading from `input` and writing to `output`.
+    pub fn new_with_io(input: I, output: O, program: Program) -> Self {
+        Intcomp {
+            memory: Memory::new(program),
+            pointer: 0,
+            relative_base: 0,
+            input,
+            output,
+            profile: None,
+        }
+    }
+
+    /// Starts counting executed instructions in `profile`, keeping any counts already there.
+    pub fn enable_profiling(&mut self) {
+        self.profile.get_or_insert_with(Profile::default);
+    }
+
+    pub fn set(&mut self, address: usize, val: i64) {
+        self.memory.set(address, val);
+    }
+
+    /// Resolves a parameter to the memory address it refers to. Immediate parameters have no
+    /// address and are rejected.
+    pub fn address(&self, val: i64, mode: Mode) -> Result<usize, IntcodeError> {
+        let pc = self.pointer;
+        let address = match mode {
+            Mode::Positional => val,
+            Mode::Relative => self
+                .relative_base
+                .checked_add(val)
+                .ok_or(IntcodeError::Overflow { pc })?,
+            Mode::Immeadiate => return Err(IntcodeError::ImmediateWrite { pc }),
+        };
+        if address < 0 {
+            return Err(IntcodeError::NegativeAddress { pc, addr: address });
+        }
+        Ok(address as usize)
+    }
+
+    pub fn get(&self, val: i64, mode: Mode) -> Result<i64, IntcodeError> {
+        match mode {
+            Mode::Immeadiate => Ok(val),
+            mode => Ok(self.memory.get(self.address(val, mode)?)),
+        }
+    }
+
+    /// Decodes the instruction at `address`. Running off the end of the program or onto a value
+    /// that isn't an instruction is a fault.
+    pub fn get_operation(&self, address: usize) -> Result<Operation, IntcodeError> {
+        if !self.memory.contains(address) {
+            return Err(IntcodeError::OutOfBounds {
+                pc: address,
+                addr: address,
+            });
+        }
+        let code = self.memory.get(address);
+        let invalid = || IntcodeError::InvalidOpcode { pc: address, code };
+        let size = Operation::size_by_code(code).map_err(|_| invalid())?;
+        let mut words = [0; MAX_OPERATION_SIZE];
+        for (offset, word) in words.iter_mut().enumerate().take(size) {
+            *word = self.memory.get(address + offset);
+        }
+        Operation::try_from(&words[..size]).map_err(|_| invalid())
+    }
+
+    /// Performs the single operation at the instruction pointer.
+    pub fn step(&mut self) -> Result<State, IntcodeError> {
+        let operation = match self.memory.cached_operation(self.pointer) {
+            Some(operation) => operation,
+            None => {
+                let operation = self.get_operation(self.pointer)?;
+                self.memory.cache_operation(self.pointer, operation);
+                operation
+            }
+        };
+        if self.profile.is_some() {
+            return self.perform_profiled(operation);
+        }
+        self.perform(operation)
+    }
+
+    /// Performs an operation and records it in the profile.
+    fn perform_profiled(&mut self, operation: Operation) -> Result<State, IntcodeError> {
+        let pointer = self.pointer;
+        let taken = match operation {
+            Operation::JumpIfTrue {
+                check, check_mode, ..
+            } => Some(self.get(check, check_mode)? != 0),
+            Operation::JumpIfFalse {
+                check, check_mode, ..
+            } => Some(self.get(check, check_mode)? == 0),
+            _ => None,
+        };
+        let write = operation.write_parameter();
+        let mut parameters = operation.parameters();
+        if write.is_some() || taken == Some(false) {
+            // the last parameter is a write target or a jump target that isn't read
+            parameters.pop();
+        }
+        let reads: Vec<usize> = parameters
+            .into_iter()
+            .filter(|(_, mode)| *mode != Mode::Immeadiate)
+            .filter_map(|(val, mode)| self.address(val, mode).ok())
+            .collect();
+        let write = match write {
+            Some((val, mode)) => Some(self.address(val, mode)?),
+            None => None,
+        };
+
+        let state = self.perform(operation)?;
+        if state != State::NeedsInput {
+            if let Some(profile) = &mut self.profile {
+                profile.record(Event {
+                    pointer,
+                    operation: &operation,
+                    reads: &reads,
+                    write,
+                    taken,
+                    next: self.pointer,
+                });
+            }
+        }
+        Ok(state)
+    }
+
+    /// Runs the program until it halts or needs input that isn't available yet.
+    pub fn run(&mut self) -> Result<State, IntcodeError> {
+        self.run_until(|_| false)
+    }
+
+    /// Runs the program until it writes a value, halts or needs input. Returns `State::Output`
+    /// with the value when one was written.
+    pub fn run_until_output(&mut self) -> Result<State, IntcodeError> {
+        self.run_until(|state| matches!(state, State::Output(_)))
+    }
+
+    /// Runs the program until it reads a value, halts or needs input. Returns `State::Input` with
+    /// the value when one was read.
+    pub fn run_until_input(&mut self) -> Result<State, IntcodeError> {
+        self.run_until(|state| matches!(state, State::Input(_)))
+    }
+
+    /// Performs at most `steps` operations, stopping early if the program halts or needs input.
+    /// Returns `State::Running` when every step was used.
+    pub fn run_for(&mut self, steps: usize) -> Result<State, IntcodeError> {
+        let mut state = State::Running;
+        for _ in 0..steps {
+            state = self.step()?;
+            if let State::NeedsInput | State::Halted = state {
+                return Ok(state);
+            }
+        }
+        Ok(match state {
+            State::Input(_) | State::Output(_) => State::Running,
+            state => state,
+        })
+    }
+
+    /// Steps the program until `stop` returns true for a state, or it halts or needs input.
+    fn run_until<F: Fn(&State) -> bool>(&mut self, stop: F) -> Result<State, IntcodeError> {
+        loop {
+            match self.step()? {
+                State::NeedsInput => return Ok(State::NeedsInput),
+                State::Halted => return Ok(State::Halted),
+                state if stop(&state) => return Ok(state),
+                _ => continue,
+            }
+        }
+    }
+
+    /// Runs the program until it halts. Running out of input is reported as
+    /// `IntcodeError::InputExhausted` rather than pausing the computer.
+    pub fn run_to_halt(&mut self) -> Result<(), IntcodeError> {
+        match self.run()? {
+            State::NeedsInput => Err(IntcodeError::InputExhausted { pc: self.pointer }),
+            _ => Ok(()),
+        }
+    }
+
+    pub fn run_debug(&mut self) -> Result<State, IntcodeError> {
+        let result = self.run();
+        Ok(result.unwrap_or_else(|err| {
+            println!("ERROR {}", err);
+            println!(
+                "memory dump: pointer={} relative_base={} {:?}",
+                self.pointer, self.relative_base, self.memory
+            );
+            panic!();
+        }))
+    }
+
+    fn jump_target(&self, val: i64, mode: Mode) -> Result<usize, IntcodeError> {
+        let target = self.get(val, mode)?;
+        if target < 0 {
+            return Err(IntcodeError::NegativeAddress {
+                pc: self.pointer,
+                addr: target,
+            });
+        }
+        Ok(target as usize)
+    }
+
+    fn overflow(&self) -> IntcodeError {
+        IntcodeError::Overflow { pc: self.pointer }
+    }
+
+    /// Performs the operation on the current computer state. Returns the state of the computer
+    /// after the operation.
+    pub fn perform(&mut self, operation: Operation) -> Result<State, IntcodeError> {
+        let op_size = operation.size();
+        match operation {
+            Operation::Add {
+                a,
+                b,
+                a_mode,
+                b_mode,
+                save,
+                save_mode,
+            } => {
+                let aval = self.get(a, a_mode)?;
+                let bval = self.get(b, b_mode)?;
+                let sum = aval.checked_add(bval).ok_or(self.overflow())?;
+                self.set(self.address(save, save_mode)?, sum);
+                self.pointer += op_size;
+                Ok(State::Running)
+            }
+            Operation::Mul {
+                a,
+                b,
+                a_mode,
+                b_mode,
+                save,
+                save_mode,
+            } => {
+                let aval = self.get(a, a_mode)?;
+                let bval = self.get(b, b_mode)?;
+                let product = aval.checked_mul(bval).ok_or(self.overflow())?;
+                self.set(self.address(save, save_mode)?, product);
+                self.pointer += op_size;
+                Ok(State::Running)
+            }
+            Operation::In { save, save_mode } => {
+                let address = self.address(save, save_mode)?;
+                let val = match self.input.read() {
+                    Some(val) => val,
+                    None => return Ok(State::NeedsInput),
+                };
+                self.set(address, val);
+                self.pointer += op_size;
+                Ok(State::Input(val))
+            }
+            Operation::Out { read, read_mode } => {
+                let val = self.get(read, read_mode)?;
+                self.output
+                    .write(val)
+                    .map_err(|err| IntcodeError::OutputFailed {
+                        pc: self.pointer,
+                        kind: err.kind(),
+                    })?;
+                self.pointer += op_size;
+                Ok(State::Output(val))
+            }
+            Operation::JumpIfTrue {
+                check,
+                address,
+                check_mode,
+                address_mode,
+            } => {
+                let check_val = self.get(check, check_mode)?;
+                if check_val != 0 {
+                    self.pointer = self.jump_target(address, address_mode)?;
+                } else {
+                    self.pointer += op_size;
+                }
+                Ok(State::Running)
+            }
+            Operation::JumpIfFalse {
+                check,
+                address,
+                check_mode,
+                address_mode,
+            } => {
+                let check_val = self.get(check, check_mode)?;
+                if check_val == 0 {
+                    self.pointer = self.jump_target(address, address_mode)?;
+                } else {
+                    self.pointer += op_size;
+                }
+                Ok(State::Running)
+            }
+            Operation::LessThan {
+                a,
+                b,
+                save,
+                a_mode,
+                b_mode,
+                save_mode,
+            } => {
+                let a_val = self.get(a, a_mode)?;
+                let b_val = self.get(b, b_mode)?;
+                let result = if a_val < b_val { 1 } else { 0 };
+                self.set(self.address(save, save_mode)?, result);
+                self.pointer += op_size;
+                Ok(State::Running)
+            }
+            Operation::Eql {
+                a,
+                b,
+                save,
+                a_mode,
+                b_mode,
+                save_mode,
+            } => {
+                let a_val = self.get(a, a_mode)?;
+                let b_val = self.get(b, b_mode)?;
+                let result = if a_val == b_val { 1 } else { 0 };
+                self.set(self.address(save, save_mode)?, result);
+                self.pointer += op_size;
+                Ok(State::Running)
+            }
+            Operation::AdjustRelativeBase { a, a_mode } => {
+                let adjustment = self.get(a, a_mode)?;
+                self.relative_base = self
+                    .relative_base
+                    .checked_add(adjustment)
+                    .ok_or(self.overflow())?;
+                self.pointer += op_size;
+                Ok(State::Running)
+            }
+            Operation::Exit => Ok(State::Halted),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::io::{LineInput, LineOutput};
+
+    fn run_program_and_check_result(program: Program, expected: &[i64]) {
+        let mut comp = Intcomp::new(program);
+        comp.run_debug().unwrap();
+        println!("result   = {:?}", &comp.memory);
+        println!("expected = {:?}", &expected);
+        assert_eq!(comp.memory.as_slice(), expected);
+    }
+
+    fn run_program_with_input(program: &[i64], input: &[i64]) -> Vec<i64> {
+        let mut comp = Intcomp::new_with_io(
+            input.iter().copied().collect::<VecDeque<_>>(),
+            Vec::new(),
+            program.to_vec(),
+        );
+        assert_eq!(comp.run_debug().unwrap(), State::Halted);
+        comp.output
+    }
+
+    #[test]
+    fn intcomp_add() {
+        run_program_and_check_result(vec![1, 0, 0, 0, 99], &[2, 0, 0, 0, 99]);
+    }
+
+    #[test]
+    fn intcomp_mul_1() {
+        run_program_and_check_result(vec![2, 3, 0, 3, 99], &[2, 3, 0, 6, 99]);
+    }
+
+    #[test]
+    fn intcomp_mul_2() {
+        run_program_and_check_result(vec![2, 4, 4, 5, 99, 0], &[2, 4, 4, 5, 99, 9801]);
+    }
+
+    #[test]
+    fn intcomp_combined_1() {
+        run_program_and_check_result(
+            vec![1, 1, 1, 4, 99, 5, 6, 0, 99],
+            &[30, 1, 1, 4, 2, 5, 6, 0, 99],
+        );
+    }
+
+    #[test]
+    fn intcomp_combined_2() {
+        run_program_and_check_result(
+            vec![1, 9, 10, 3, 2, 3, 11, 0, 99, 30, 40, 50],
+            &[3500, 9, 10, 70, 2, 3, 11, 0, 99, 30, 40, 50],
+        );
+    }
+
+    #[test]
+    fn stdin_stdout() {
+        let stdin = LineInput::new(&b"94"[..]);
+        let stdout = LineOutput::new(Vec::new());
+        let mut comp = Intcomp::new_with_io(stdin, stdout, vec![3, 0, 4, 0, 99]);
+        comp.run_debug().unwrap();
+        assert_eq!(comp.memory[0], 94);
+        let output = String::from_utf8(comp.output.into_inner()).unwrap();
+        assert_eq!(output, "94\n");
+    }
+
+    #[test]
+    fn needs_input() {
+        let mut comp = Intcomp::new(vec![3, 0, 4, 0, 99]);
+        assert_eq!(comp.run().unwrap(), State::NeedsInput);
+        assert_eq!(comp.pointer, 0);
+        comp.input.push_back(7);
+        assert_eq!(comp.run().unwrap(), State::Halted);
+        assert_eq!(comp.output, vec![7]);
+        assert_eq!(comp.run().unwrap(), State::Halted);
+    }
+
+    #[test]
+    fn step() {
+        let mut comp = Intcomp::new(vec![3, 0, 4, 0, 99]);
+        comp.input.push_back(7);
+        assert_eq!(comp.step().unwrap(), State::Input(7));
+        assert_eq!(comp.pointer, 2);
+        assert_eq!(comp.step().unwrap(), State::Output(7));
+        assert_eq!(comp.step().unwrap(), State::Halted);
+        assert_eq!(comp.step().unwrap(), State::Halted);
+    }
+
+    #[test]
+    fn run_until_output() {
+        let mut comp = Intcomp::new(vec![104, 1, 104, 2, 3, 0, 99]);
+        assert_eq!(comp.run_until_output().unwrap(), State::Output(1));
+        assert_eq!(comp.run_until_output().unwrap(), State::Output(2));
+        assert_eq!(comp.run_until_output().unwrap(), State::NeedsInput);
+        comp.input.push_back(5);
+        assert_eq!(comp.run_until_output().unwrap(), State::Halted);
+        assert_eq!(comp.output, vec![1, 2]);
+    }
+
+    #[test]
+    fn run_until_input() {
+        let mut comp = Intcomp::new(vec![104, 1, 3, 0, 3, 1, 99]);
+        comp.input.extend(vec![5, 6]);
+        assert_eq!(comp.run_until_input().unwrap(), State::Input(5));
+        assert_eq!(comp.output, vec![1]);
+        assert_eq!(comp.run_until_input().unwrap(), State::Input(6));
+        assert_eq!(comp.run_until_input().unwrap(), State::Halted);
+    }
+
+    #[test]
+    fn run_for() {
+        let mut comp = Intcomp::new(vec![1101, 1, 1, 0, 104, 3, 99]);
+        assert_eq!(comp.run_for(2).unwrap(), State::Running);
+        assert_eq!(comp.memory[0], 2);
+        assert_eq!(comp.output, vec![3]);
+        assert_eq!(comp.run_for(5).unwrap(), State::Halted);
+        assert_eq!(comp.run_for(0).unwrap(), State::Running);
+    }
+
+    #[test]
+    fn self_modifying_code() {
+        // writes over the operand of the output instruction, then over its opcode
+        let program = crate::assemble::assemble(
+            "
+            start:  out #1
+                    add [count], #1, [count]
+                    eq [count], #3, [done]
+                    jt [done], #stop
+                    add [start+1], #1, [start+1]
+                    jt #1, #start
+            stop:   add #0, #99, [start]
+                    jt #1, #start
+            count:  .data 0
+            done:   .data 0
+            ",
+        )
+        .unwrap();
+        let mut comp = Intcomp::new(program);
+        assert_eq!(comp.run_for(100).unwrap(), State::Halted);
+        assert_eq!(comp.output, vec![1, 2, 3]);
+        assert_eq!(comp.pointer, 0);
+    }
+
+    #[test]
+    fn snapshot_restore() {
+        // echo inputs until a 0 is read
+        let mut comp = Intcomp::new(vec![3, 9, 4, 9, 1005, 9, 0, 99, 0, 0]);
+        comp.input.push_back(1);
+        assert_eq!(comp.run().unwrap(), State::NeedsInput);
+        let snapshot = comp.snapshot();
+
+        comp.input.extend(vec![2, 0]);
+        assert_eq!(comp.run().unwrap(), State::Halted);
+        assert_eq!(comp.output, vec![1, 2, 0]);
+
+        comp.restore(&snapshot);
+        assert_eq!(comp.output, vec![1]);
+        comp.input.push_back(0);
+        assert_eq!(comp.run().unwrap(), State::Halted);
+        assert_eq!(comp.output, vec![1, 0]);
+
+        let mut fork = Intcomp::from(snapshot);
+        fork.input.extend(vec![4, 0]);
+        assert_eq!(fork.run().unwrap(), State::Halted);
+        assert_eq!(fork.output, vec![1, 4, 0]);
+    }
+
+    #[test]
+    fn position_mode_eql() {
+        let program = [3, 9, 8, 9, 10, 9, 4, 9, 99, -1, 8];
+        assert_eq!(run_program_with_input(&program, &[8]), vec![1]);
+        assert_eq!(run_program_with_input(&program, &[9]), vec![0]);
+    }
+
+    #[test]
+    fn position_mode_less_than() {
+        let program = [3, 9, 7, 9, 10, 9, 4, 9, 99, -1, 8];
+        assert_eq!(run_program_with_input(&program, &[5]), vec![1]);
+        assert_eq!(run_program_with_input(&program, &[9]), vec![0]);
+    }
+
+    #[test]
+    fn immeadiate_mode_eql() {
+        let program = [3, 3, 1108, -1, 8, 3, 4, 3, 99];
+        assert_eq!(run_program_with_input(&program, &[8]), vec![1]);
+        assert_eq!(run_program_with_input(&program, &[9]), vec![0]);
+    }
+
+    #[test]
+    fn immeadiate_mode_less_than() {
+        let program = [3, 3, 1107, -1, 8, 3, 4, 3, 99];
+        assert_eq!(run_program_with_input(&program, &[5]), vec![1]);
+        assert_eq!(run_program_with_input(&program, &[10]), vec![0]);
+    }
+
+    #[test]
+    fn immeadiate_mode_jump() {
+        let program = [3, 3, 1105, -1, 9, 1101, 0, 0, 12, 4, 12, 99, 1];
+        assert_eq!(run_program_with_input(&program, &[10]), vec![1]);
+        assert_eq!(run_program_with_input(&program, &[0]), vec![0]);
+    }
+
+    #[test]
+    fn intcomp_combined_3() {
+        let program = [
+            3, 21, 1008, 21, 8, 20, 1005, 20, 22, 107, 8, 21, 20, 1006, 20, 31, 1106, 0, 36, 98, 0,
+            0, 1002, 21, 125, 20, 4, 20, 1105, 1, 46, 104, 999, 1105, 1, 46, 1101, 1000, 1, 20, 4,
+            20, 1105, 1, 46, 98, 99,
+        ];
+        assert_eq!(run_program_with_input(&program, &[5]), vec![999]);
+        assert_eq!(run_program_with_input(&program, &[8]), vec![1000]);
+        assert_eq!(run_program_with_input(&program, &[300]), vec![1001]);
+    }
+
+    #[test]
+    fn adjust_relative_base() {
+        let mut comp = Intcomp::new(vec![109, 19, 204, -34, 99]);
+        comp.relative_base = 2000;
+        comp.memory.set(1985, 42);
+        comp.run_debug().unwrap();
+        assert_eq!(comp.relative_base, 2019);
+        assert_eq!(comp.output, vec![42]);
+
+        // a negative relative address is a fault, not a wrapped index
+        let mut comp = Intcomp::new(vec![109, 19, 204, -34, 99]);
+        assert_eq!(
+            comp.run(),
+            Err(IntcodeError::NegativeAddress { pc: 2, addr: -15 })
+        );
+    }
+
+    #[test]
+    fn faults() {
+        let run = |program: Vec<i64>| Intcomp::new(program).run().unwrap_err();
+        assert_eq!(
+            run(vec![1, 0, 0, 0, 42]),
+            IntcodeError::InvalidOpcode { pc: 4, code: 42 }
+        );
+        assert_eq!(
+            run(vec![1, 0, 0, 0]),
+            IntcodeError::OutOfBounds { pc: 4, addr: 4 }
+        );
+        assert_eq!(
+            run(vec![1105, 1, -3]),
+            IntcodeError::NegativeAddress { pc: 0, addr: -3 }
+        );
+        assert_eq!(
+            run(vec![11101, 1, 1, 3]),
+            IntcodeError::ImmediateWrite { pc: 0 }
+        );
+
+        // arithmetic past the range of i64 is a fault rather than a panic or a wrapped value
+        let max = i64::MAX;
+        assert_eq!(
+            run(vec![1101, max, 1, 0, 99]),
+            IntcodeError::Overflow { pc: 0 }
+        );
+        assert_eq!(
+            run(vec![1101, 2, 1, 0, 1102, max, 2, 0, 99]),
+            IntcodeError::Overflow { pc: 4 }
+        );
+        assert_eq!(
+            run(vec![109, max, 109, 1, 99]),
+            IntcodeError::Overflow { pc: 2 }
+        );
+        assert_eq!(
+            run(vec![109, max, 204, 1, 99]),
+            IntcodeError::Overflow { pc: 2 }
+        );
+
+        // starving for input pauses a plain run but is a fault when running to completion
+        let mut comp = Intcomp::new(vec![3, 0, 99]);
+        assert_eq!(comp.run(), Ok(State::NeedsInput));
+        assert_eq!(
+            comp.run_to_halt(),
+            Err(IntcodeError::InputExhausted { pc: 0 })
+        );
+        comp.input.push_back(1);
+        assert_eq!(comp.run_to_halt(), Ok(()));
+    }
+
+    #[test]
+    fn relative_mode_write() {
+        // input is saved at relative_base + 3, then read back through position mode
+        let output = run_program_with_input(&[109, 4, 203, 3, 4, 7, 99, 0], &[7]);
+        assert_eq!(output, vec![7]);
+    }
+
+    #[test]
+    fn quine() {
+        let program = vec![
+            109, 1, 204, -1, 1001, 100, 1, 100, 1008, 100, 16, 101, 1006, 101, 0, 99,
+        ];
+        assert_eq!(run_program_with_input(&program, &[]), program);
+    }
+
+    #[test]
+    fn large_numbers() {
+        let output = run_program_with_input(&[1102, 34915192, 34915192, 7, 4, 7, 99, 0], &[]);
+        assert_eq!(output, vec![1219070632396864]);
+
+        let output = run_program_with_input(&[104, 1125899906842624, 99], &[]);
+        assert_eq!(output, vec![1125899906842624]);
+    }
+
+    #[test]
+    fn write_beyond_program() {
+        let mut comp = Intcomp::new(vec![1101, 2, 3, 1000000, 99]);
+        comp.run_debug().unwrap();
+        assert_eq!(comp.memory[1000000], 5);
+        assert_eq!(comp.memory.dense_len(), 5);
+    }
+}
@@ -0,0 +1,270 @@
+//! Input sources and output sinks for the Intcode computer.
+//!
+//! The computer only ever deals in `i64` values. Reading never blocks the machine: when a source
+//! has nothing available `Intcomp::run` returns `State::NeedsInput` and can be resumed once more
+//! input has been provided.
+use std::collections::VecDeque;
+use std::convert::TryFrom;
+use std::io::{self, BufRead, Write};
+use std::sync::mpsc::{Receiver, Sender};
+
+/// A source of values for the input instruction (opcode 3).
+pub trait Input {
+    /// Takes the next value, or `None` when nothing is available yet.
+    fn read(&mut self) -> Option<i64>;
+}
+
+/// A sink for values from the output instruction (opcode 4).
+pub trait Output {
+    fn write(&mut self, val: i64) -> io::Result<()>;
+}
+
+impl Input for VecDeque<i64> {
+    fn read(&mut self) -> Option<i64> {
+        self.pop_front()
+    }
+}
+
+impl Output for VecDeque<i64> {
+    fn write(&mut self, val: i64) -> io::Result<()> {
+        self.push_back(val);
+        Ok(())
+    }
+}
+
+impl Output for Vec<i64> {
+    fn write(&mut self, val: i64) -> io::Result<()> {
+        self.push(val);
+        Ok(())
+    }
+}
+
+impl Input for Receiver<i64> {
+    fn read(&mut self) -> Option<i64> {
+        self.try_recv().ok()
+    }
+}
+
+impl Output for Sender<i64> {
+    fn write(&mut self, val: i64) -> io::Result<()> {
+        self.send(val)
+            .map_err(|_| io::Error::new(io::ErrorKind::BrokenPipe, "output receiver dropped"))
+    }
+}
+
+impl<T: Input + ?Sized> Input for &mut T {
+    fn read(&mut self) -> Option<i64> {
+        (**self).read()
+    }
+}
+
+impl<T: Output + ?Sized> Output for &mut T {
+    fn write(&mut self, val: i64) -> io::Result<()> {
+        (**self).write(val)
+    }
+}
+
+/// Reads one integer per line from a text reader, such as a locked stdin. Blank and unparsable
+/// lines are skipped. The source is exhausted at the end of the reader.
+#[derive(Debug)]
+pub struct LineInput<R: BufRead> {
+    reader: R,
+}
+
+impl<R: BufRead> LineInput<R> {
+    pub fn new(reader: R) -> Self {
+        LineInput { reader }
+    }
+}
+
+impl<R: BufRead> Input for LineInput<R> {
+    fn read(&mut self) -> Option<i64> {
+        let mut line = String::new();
+        loop {
+            line.clear();
+            match self.reader.read_line(&mut line) {
+                Ok(0) | Err(_) => return None,
+                Ok(_) => {
+                    if let Ok(val) = line.trim().parse() {
+                        return Some(val);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Writes each output value as a line of text.
+#[derive(Debug)]
+pub struct LineOutput<W: Write> {
+    writer: W,
+}
+
+impl<W: Write> LineOutput<W> {
+    pub fn new(writer: W) -> Self {
+        LineOutput { writer }
+    }
+
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+impl<W: Write> Output for LineOutput<W> {
+    fn write(&mut self, val: i64) -> io::Result<()> {
+        writeln!(self.writer, "{}", val)?;
+        self.writer.flush()
+    }
+}
+
+/// Converts a line of text to the character codes an ASCII program expects, ending in a newline
+/// (10). Characters outside of ASCII are dropped.
+pub fn encode_line(line: &str) -> Vec<i64> {
+    line.trim_end_matches(['\r', '\n'])
+        .bytes()
+        .filter(u8::is_ascii)
+        .map(i64::from)
+        .chain(Some(10))
+        .collect()
+}
+
+/// Converts character codes back to text. Values outside of ASCII, such as a final numeric answer,
+/// are written as numbers on a line of their own.
+pub fn decode(values: &[i64]) -> String {
+    let mut output = AsciiOutput::new(Vec::new());
+    for val in values {
+        output.write(*val).expect("writing to a Vec can't fail");
+    }
+    String::from_utf8(output.into_inner()).expect("only ASCII is written")
+}
+
+/// Reads lines of text and gives them to the program one character code at a time, each line
+/// ending with a newline. The source is exhausted at the end of the reader.
+#[derive(Debug)]
+pub struct AsciiInput<R: BufRead> {
+    reader: R,
+    pending: VecDeque<i64>,
+}
+
+impl<R: BufRead> AsciiInput<R> {
+    pub fn new(reader: R) -> Self {
+        AsciiInput {
+            reader,
+            pending: VecDeque::new(),
+        }
+    }
+}
+
+impl<R: BufRead> Input for AsciiInput<R> {
+    fn read(&mut self) -> Option<i64> {
+        if self.pending.is_empty() {
+            let mut line = String::new();
+            match self.reader.read_line(&mut line) {
+                Ok(0) | Err(_) => return None,
+                Ok(_) => self.pending.extend(encode_line(&line)),
+            }
+        }
+        self.pending.pop_front()
+    }
+}
+
+/// Writes character codes as text. Values outside of ASCII are passed through as numbers on a line
+/// of their own.
+#[derive(Debug)]
+pub struct AsciiOutput<W: Write> {
+    writer: W,
+    /// Whether the last thing written ended a line.
+    line_start: bool,
+}
+
+impl<W: Write> AsciiOutput<W> {
+    pub fn new(writer: W) -> Self {
+        AsciiOutput {
+            writer,
+            line_start: true,
+        }
+    }
+
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+impl<W: Write> Output for AsciiOutput<W> {
+    fn write(&mut self, val: i64) -> io::Result<()> {
+        match u8::try_from(val) {
+            Ok(byte) if byte.is_ascii() => {
+                self.writer.write_all(&[byte])?;
+                self.line_start = byte == b'\n';
+                if self.line_start {
+                    self.writer.flush()?;
+                }
+            }
+            _ => {
+                if !self.line_start {
+                    writeln!(self.writer)?;
+                }
+                writeln!(self.writer, "{}", val)?;
+                self.line_start = true;
+                self.writer.flush()?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::mpsc::channel;
+
+    #[test]
+    fn line_input() {
+        let mut input = LineInput::new(&b"1\n\nx\n-20\n"[..]);
+        assert_eq!(input.read(), Some(1));
+        assert_eq!(input.read(), Some(-20));
+        assert_eq!(input.read(), None);
+    }
+
+    #[test]
+    fn line_output() {
+        let mut output = LineOutput::new(Vec::new());
+        output.write(3).unwrap();
+        output.write(-4).unwrap();
+        assert_eq!(output.into_inner(), b"3\n-4\n");
+    }
+
+    #[test]
+    fn channels() {
+        let (mut tx, mut rx) = channel();
+        assert_eq!(rx.read(), None);
+        tx.write(5).unwrap();
+        assert_eq!(rx.read(), Some(5));
+        drop(rx);
+        assert!(tx.write(6).is_err());
+    }
+
+    #[test]
+    fn ascii_input() {
+        let mut input = AsciiInput::new(&b"ab\r\n\nNOT\n"[..]);
+        let mut codes = Vec::new();
+        while let Some(val) = input.read() {
+            codes.push(val);
+        }
+        assert_eq!(codes, vec![97, 98, 10, 10, 78, 79, 84, 10]);
+        assert_eq!(encode_line("WALK"), vec![87, 65, 76, 75, 10]);
+    }
+
+    #[test]
+    fn ascii_output() {
+        let mut output = AsciiOutput::new(Vec::new());
+        for val in encode_line("#.") {
+            output.write(val).unwrap();
+        }
+        output.write(19_355_645).unwrap();
+        output.write(b'>' as i64).unwrap();
+        output.write(-1).unwrap();
+        assert_eq!(output.into_inner(), b"#.\n19355645\n>\n-1\n");
+        assert_eq!(decode(&[104, 105, 10, 1000]), "hi\n1000\n");
+    }
+}
@@ -0,0 +1,30 @@
+//! The Intcode computer shared by the 2019 puzzles.
+//!
+//! Day 2 introduced the computer and day 5 extended it with I/O, parameter modes and jumps. Every
+//! later Intcode puzzle builds on the same machine so it lives here instead of in a single day's
+//! binary.
+use std::error::Error;
+
+pub mod assemble;
+pub mod debugger;
+pub mod disassemble;
+mod error;
+pub mod grid;
+mod intcomp;
+pub mod io;
+mod memory;
+pub mod network;
+mod operation;
+pub mod profile;
+mod program;
+pub mod search;
+
+pub use error::IntcodeError;
+pub use intcomp::{Intcomp, Snapshot, State};
+pub use io::{Input, Output};
+pub use memory::Memory;
+pub use network::{MachineError, Network, NetworkState};
+pub use operation::{Mode, Operation, Parameter};
+pub use program::{load_program_file, parse_program, Program};
+
+pub type BoxResult<T> = Result<T, Box<dyn Error>>;
@@ -0,0 +1,212 @@
+use std::collections::BTreeMap;
+use std::ops::Index;
+
+use crate::operation::{Operation, MAX_OPERATION_SIZE};
+
+/// How far past the end of the dense memory a write may land before it is stored sparsely
+/// instead of growing the dense memory.
+const DENSE_GROWTH_WINDOW: usize = 4096;
+
+/// Intcode memory. Reads past the end of the program return 0 and writes extend the memory.
+/// Memory can be read by indexing, but only written with `set`, so a read never adds a cell.
+///
+/// Addresses near the program image are stored in a `Vec`. Writes far beyond it go into a sparse
+/// map so a program poking at a very high address doesn't need a huge allocation.
+///
+/// Memory also caches the instructions decoded from it so a loop doesn't decode the same
+/// instruction on every pass. Every write clears the cached instructions the written cell could be
+/// part of, so self-modifying programs always run the current code.
+#[derive(Debug, Clone, Default)]
+pub struct Memory {
+    dense: Vec<i64>,
+    sparse: BTreeMap<usize, i64>,
+    decoded: Vec<Option<Operation>>,
+}
+
+impl Memory {
+    pub fn new(program: Vec<i64>) -> Self {
+        Memory {
+            dense: program,
+            sparse: BTreeMap::new(),
+            decoded: Vec::new(),
+        }
+    }
+
+    pub fn get(&self, address: usize) -> i64 {
+        match self.dense.get(address) {
+            Some(val) => *val,
+            None => self.sparse.get(&address).copied().unwrap_or(0),
+        }
+    }
+
+    pub fn set(&mut self, address: usize, val: i64) {
+        let first = address.saturating_sub(MAX_OPERATION_SIZE - 1);
+        for entry in self.decoded.iter_mut().take(address + 1).skip(first) {
+            *entry = None;
+        }
+        if address >= self.dense.len() && address - self.dense.len() <= DENSE_GROWTH_WINDOW {
+            self.grow(address + 1);
+        }
+        if address < self.dense.len() {
+            self.dense[address] = val;
+        } else {
+            self.sparse.insert(address, val);
+        }
+    }
+
+    /// Whether `address` is part of the program image or has been written to.
+    pub fn contains(&self, address: usize) -> bool {
+        address < self.dense.len() || self.sparse.contains_key(&address)
+    }
+
+    /// The number of cells in the dense part of memory. Cells stored sparsely aren't counted.
+    pub fn dense_len(&self) -> usize {
+        self.dense.len()
+    }
+
+    /// Whether no cell has been loaded or written, in either part of memory.
+    pub fn is_empty(&self) -> bool {
+        self.dense.is_empty() && self.sparse.is_empty()
+    }
+
+    /// The dense part of memory, starting at address 0.
+    pub fn as_slice(&self) -> &[i64] {
+        &self.dense
+    }
+
+    /// Cells that were written far beyond the dense part of memory, ordered by address.
+    pub fn sparse_cells(&self) -> impl Iterator<Item = (usize, i64)> + '_ {
+        self.sparse.iter().map(|(address, val)| (*address, *val))
+    }
+
+    /// The instruction previously cached at `address`, if nothing has been written over it since.
+    pub(crate) fn cached_operation(&self, address: usize) -> Option<Operation> {
+        self.decoded.get(address).copied().flatten()
+    }
+
+    /// Caches the instruction decoded at `address`. Only the dense part of memory is cached.
+    pub(crate) fn cache_operation(&mut self, address: usize, operation: Operation) {
+        if address + operation.size() > self.dense.len() {
+            return;
+        }
+        if self.decoded.len() < self.dense.len() {
+            self.decoded.resize(self.dense.len(), None);
+        }
+        self.decoded[address] = Some(operation);
+    }
+
+    /// Extends the dense memory to `len` cells, pulling in any sparse cells it now covers.
+    fn grow(&mut self, len: usize) {
+        let start = self.dense.len();
+        self.dense.resize(len, 0);
+        let covered: Vec<usize> = self.sparse.range(start..len).map(|(a, _)| *a).collect();
+        for address in covered {
+            if let Some(val) = self.sparse.remove(&address) {
+                self.dense[address] = val;
+            }
+        }
+    }
+}
+
+impl PartialEq for Memory {
+    fn eq(&self, other: &Self) -> bool {
+        self.dense == other.dense && self.sparse == other.sparse
+    }
+}
+
+impl From<Vec<i64>> for Memory {
+    fn from(program: Vec<i64>) -> Self {
+        Memory::new(program)
+    }
+}
+
+impl Index<usize> for Memory {
+    type Output = i64;
+
+    fn index(&self, address: usize) -> &i64 {
+        static ZERO: i64 = 0;
+        match self.dense.get(address) {
+            Some(val) => val,
+            None => self.sparse.get(&address).unwrap_or(&ZERO),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::convert::TryFrom;
+
+    #[test]
+    fn read_past_end() {
+        let memory = Memory::new(vec![1, 2, 3]);
+        assert_eq!(memory.get(2), 3);
+        assert_eq!(memory.get(3), 0);
+        assert_eq!(memory[1_000_000_000], 0);
+        // reads don't add cells
+        assert_eq!(memory.sparse_cells().count(), 0);
+        assert_eq!(memory.dense_len(), 3);
+    }
+
+    #[test]
+    fn write_grows_dense() {
+        let mut memory = Memory::new(vec![1, 2, 3]);
+        memory.set(10, 7);
+        assert_eq!(memory.dense_len(), 11);
+        assert_eq!(memory.as_slice(), &[1, 2, 3, 0, 0, 0, 0, 0, 0, 0, 7]);
+        assert_eq!(memory.sparse_cells().count(), 0);
+    }
+
+    #[test]
+    fn empty() {
+        let mut memory = Memory::new(vec![]);
+        assert!(memory.is_empty());
+        memory.set(1 << 40, 1);
+        assert_eq!(memory.dense_len(), 0);
+        assert!(!memory.is_empty());
+    }
+
+    #[test]
+    fn write_far_is_sparse() {
+        let mut memory = Memory::new(vec![1, 2, 3]);
+        memory.set(1 << 40, 9);
+        assert_eq!(memory.dense_len(), 3);
+        assert_eq!(memory.get(1 << 40), 9);
+        assert_eq!(
+            memory.sparse_cells().collect::<Vec<_>>(),
+            vec![(1 << 40, 9)]
+        );
+    }
+
+    #[test]
+    fn growth_absorbs_sparse_cells() {
+        let mut memory = Memory::new(vec![]);
+        memory.set(DENSE_GROWTH_WINDOW + 10, 5);
+        assert_eq!(memory.dense_len(), 0);
+        memory.set(DENSE_GROWTH_WINDOW, 1);
+        memory.set(DENSE_GROWTH_WINDOW + 20, 2);
+        assert_eq!(memory.dense_len(), DENSE_GROWTH_WINDOW + 21);
+        assert_eq!(memory.sparse_cells().count(), 0);
+        assert_eq!(memory[DENSE_GROWTH_WINDOW + 10], 5);
+    }
+
+    #[test]
+    fn writes_clear_cached_operations() {
+        let mut memory = Memory::new(vec![1101, 1, 2, 7, 99, 0, 0, 0]);
+        let add = Operation::try_from(&memory.as_slice()[..4]).unwrap();
+        memory.cache_operation(0, add);
+        memory.cache_operation(4, Operation::Exit);
+        assert_eq!(memory.cached_operation(0), Some(add));
+
+        // a write to the last parameter clears the instruction but not the one after it
+        memory.set(3, 6);
+        assert_eq!(memory.cached_operation(0), None);
+        assert_eq!(memory.cached_operation(4), Some(Operation::Exit));
+        memory.set(4, 1);
+        assert_eq!(memory.cached_operation(4), None);
+
+        // cached instructions don't change equality
+        memory.cache_operation(4, Operation::Exit);
+        assert_eq!(memory, Memory::new(vec![1101, 1, 2, 6, 1, 0, 0, 0]));
+    }
+}
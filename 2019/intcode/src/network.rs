@@ -0,0 +1,347 @@
+//! Runs several Intcode computers together, passing the outputs of one machine to the inputs of
+//! others.
+//!
+//! Machines are run in turn, each until it blocks on input or halts. After a machine runs its
+//! outputs are delivered according to its `Route`, so the network can be wired as a chain, a ring
+//! feedback loop or a packet switched network where each output names its destination.
+use std::collections::VecDeque;
+use std::error::Error;
+use std::fmt;
+
+use crate::error::IntcodeError;
+use crate::intcomp::{Intcomp, State};
+use crate::program::Program;
+
+pub type Machine = Intcomp<VecDeque<i64>, VecDeque<i64>>;
+
+/// Where a machine's output values are sent.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Route {
+    /// Outputs stay in the machine's output queue for the caller to collect.
+    Keep,
+    /// Every output value is copied to the input of each of these machines.
+    Machines(Vec<usize>),
+    /// Outputs are grouped into packets of `size` values. The first value of a packet is the
+    /// destination machine and the rest are appended to its input. Packets addressed outside the
+    /// network are collected in the network's outbox.
+    Packets { size: usize },
+}
+
+/// A packet sent to an address outside of the network.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Packet {
+    pub from: usize,
+    pub to: i64,
+    pub data: Vec<i64>,
+}
+
+/// The state of the network after a round of running every machine.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NetworkState {
+    /// At least one machine read or wrote a value during the round.
+    Running,
+    /// Every machine has halted.
+    Halted,
+    /// Every running machine only read the network's idle input value and nothing was sent.
+    Idle,
+    /// Every running machine is waiting for input and there is no idle input to give them.
+    Deadlock,
+}
+
+/// A fault in one of the network's machines.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MachineError {
+    pub machine: usize,
+    pub error: IntcodeError,
+}
+
+impl fmt::Display for MachineError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "machine {}: {}", self.machine, self.error)
+    }
+}
+
+impl Error for MachineError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        Some(&self.error)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Network {
+    pub machines: Vec<Machine>,
+    routes: Vec<Route>,
+    halted: Vec<bool>,
+    last_output: Vec<Option<i64>>,
+    /// A value given to a machine that asks for input while its queue is empty. Without one a
+    /// machine waiting on an empty queue blocks until another machine sends it a value.
+    pub idle_input: Option<i64>,
+    /// Packets addressed outside of the network, in the order they were sent.
+    pub outbox: VecDeque<Packet>,
+}
+
+impl Network {
+    /// Creates a network of unconnected machines.
+    pub fn new(machines: Vec<Machine>) -> Self {
+        let n = machines.len();
+        Network {
+            machines,
+            routes: vec![Route::Keep; n],
+            halted: vec![false; n],
+            last_output: vec![None; n],
+            idle_input: None,
+            outbox: VecDeque::new(),
+        }
+    }
+
+    /// Creates `n` copies of `program` where each machine's output feeds the next machine and the
+    /// last machine feeds the first.
+    pub fn ring(program: &Program, n: usize) -> Self {
+        let mut network = Network::new(vec![Intcomp::new(program.clone()); n]);
+        for i in 0..n {
+            network.connect(i, (i + 1) % n);
+        }
+        network
+    }
+
+    /// Creates `n` copies of `program` that talk to each other with packets of `size` values.
+    /// Each machine is given its address as its first input.
+    pub fn packets(program: &Program, n: usize, size: usize) -> Self {
+        let mut network = Network::new(vec![Intcomp::new(program.clone()); n]);
+        for i in 0..n {
+            network.set_route(i, Route::Packets { size });
+            network.machines[i].input.push_back(i as i64);
+        }
+        network
+    }
+
+    /// Panics if `route` groups packets of no values, as every packet needs an address.
+    pub fn set_route(&mut self, machine: usize, route: Route) {
+        assert!(
+            route != Route::Packets { size: 0 },
+            "packets must hold at least an address"
+        );
+        self.routes[machine] = route;
+    }
+
+    /// Sends the output of machine `from` to the input of machine `to`, in addition to any other
+    /// machines it already sends to. Panics if `to` isn't a machine of the network.
+    pub fn connect(&mut self, from: usize, to: usize) {
+        assert!(
+            to < self.machines.len(),
+            "cannot connect to machine {} of {}",
+            to,
+            self.machines.len()
+        );
+        match &mut self.routes[from] {
+            Route::Machines(targets) => targets.push(to),
+            route => *route = Route::Machines(vec![to]),
+        }
+    }
+
+    /// Appends values to a machine's input.
+    pub fn send(&mut self, to: usize, values: &[i64]) {
+        self.machines[to].input.extend(values);
+    }
+
+    /// The most recent value written by a machine, even if it has since been delivered.
+    pub fn last_output(&self, machine: usize) -> Option<i64> {
+        self.last_output[machine]
+    }
+
+    pub fn is_halted(&self, machine: usize) -> bool {
+        self.halted[machine]
+    }
+
+    /// Runs rounds until the network halts, goes idle or deadlocks.
+    pub fn run(&mut self) -> Result<NetworkState, MachineError> {
+        loop {
+            match self.round()? {
+                NetworkState::Running => continue,
+                state => return Ok(state),
+            }
+        }
+    }
+
+    /// Runs every machine once, in order, until it blocks on input or halts, delivering its
+    /// outputs before moving to the next machine.
+    pub fn round(&mut self) -> Result<NetworkState, MachineError> {
+        let mut progress = false;
+        for i in 0..self.machines.len() {
+            if self.halted[i] {
+                continue;
+            }
+            let machine = &mut self.machines[i];
+            let queued = machine.input.len();
+            let written = machine.output.len();
+            if queued == 0 {
+                if let Some(val) = self.idle_input {
+                    machine.input.push_back(val);
+                }
+            }
+            let state = machine
+                .run()
+                .map_err(|error| MachineError { machine: i, error })?;
+            self.halted[i] = state == State::Halted;
+            // output left over from earlier rounds, such as a partial packet, isn't progress
+            if queued > machine.input.len() || machine.output.len() > written {
+                progress = true;
+            }
+            self.deliver(i);
+        }
+
+        Ok(if self.halted.iter().all(|halted| *halted) {
+            NetworkState::Halted
+        } else if progress {
+            NetworkState::Running
+        } else if self.idle_input.is_some() {
+            NetworkState::Idle
+        } else {
+            NetworkState::Deadlock
+        })
+    }
+
+    /// Moves the pending output of machine `from` to its destinations.
+    fn deliver(&mut self, from: usize) {
+        if let Some(val) = self.machines[from].output.back() {
+            self.last_output[from] = Some(*val);
+        }
+        match &self.routes[from] {
+            Route::Keep => {}
+            Route::Machines(targets) => {
+                let values: Vec<i64> = self.machines[from].output.drain(..).collect();
+                for to in targets.clone() {
+                    self.machines[to].input.extend(&values);
+                }
+            }
+            Route::Packets { size } => {
+                let size = *size;
+                while self.machines[from].output.len() >= size {
+                    let mut packet: Vec<i64> = self.machines[from].output.drain(..size).collect();
+                    let to = packet.remove(0);
+                    if to >= 0 && (to as usize) < self.machines.len() {
+                        self.machines[to as usize].input.extend(packet);
+                    } else {
+                        self.outbox.push_back(Packet {
+                            from,
+                            to,
+                            data: packet,
+                        });
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn amplifiers(program: &Program, phases: &[i64], feedback: bool) -> i64 {
+        let mut network = if feedback {
+            Network::ring(program, phases.len())
+        } else {
+            let mut network = Network::new(vec![Intcomp::new(program.clone()); phases.len()]);
+            for i in 1..phases.len() {
+                network.connect(i - 1, i);
+            }
+            network
+        };
+        for (i, phase) in phases.iter().enumerate() {
+            network.send(i, &[*phase]);
+        }
+        network.send(0, &[0]);
+        assert_eq!(network.run().unwrap(), NetworkState::Halted);
+        network.last_output(phases.len() - 1).unwrap()
+    }
+
+    #[test]
+    fn amplifier_chain() {
+        let program = vec![
+            3, 15, 3, 16, 1002, 16, 10, 16, 1, 16, 15, 15, 4, 15, 99, 0, 0,
+        ];
+        assert_eq!(amplifiers(&program, &[4, 3, 2, 1, 0], false), 43210);
+
+        let program = vec![
+            3, 23, 3, 24, 1002, 24, 10, 24, 1002, 23, -1, 23, 101, 5, 23, 23, 1, 24, 23, 23, 4, 23,
+            99, 0, 0,
+        ];
+        assert_eq!(amplifiers(&program, &[0, 1, 2, 3, 4], false), 54321);
+    }
+
+    #[test]
+    fn amplifier_feedback_loop() {
+        let program = vec![
+            3, 26, 1001, 26, -4, 26, 3, 27, 1002, 27, 2, 27, 1, 27, 26, 27, 4, 27, 1001, 28, -1,
+            28, 1005, 28, 6, 99, 0, 0, 5,
+        ];
+        assert_eq!(amplifiers(&program, &[9, 8, 7, 6, 5], true), 139629729);
+    }
+
+    #[test]
+    fn deadlock() {
+        // each machine echoes its input forever
+        let mut network = Network::ring(&vec![3, 7, 4, 7, 1105, 1, 0, 0], 2);
+        assert_eq!(network.run().unwrap(), NetworkState::Deadlock);
+        network.send(0, &[5]);
+        assert_eq!(network.round().unwrap(), NetworkState::Running);
+        assert_eq!(network.last_output(1), Some(5));
+        assert_eq!(network.machines[0].input, vec![5]);
+    }
+
+    #[test]
+    fn kept_output_is_not_progress() {
+        // outputs 1 then waits for input that never comes
+        let program = vec![104, 1, 3, 10, 99];
+        let mut network = Network::new(vec![Intcomp::new(program.clone())]);
+        assert_eq!(network.round().unwrap(), NetworkState::Running);
+        assert_eq!(network.round().unwrap(), NetworkState::Deadlock);
+        assert_eq!(network.machines[0].output, vec![1]);
+
+        // a partial packet stays queued until the rest of it is written
+        let mut network = Network::new(vec![Intcomp::new(program)]);
+        network.set_route(0, Route::Packets { size: 3 });
+        assert_eq!(network.run().unwrap(), NetworkState::Deadlock);
+        assert_eq!(network.machines[0].output, vec![1]);
+    }
+
+    #[test]
+    #[should_panic(expected = "cannot connect to machine 2 of 2")]
+    fn connect_missing_machine() {
+        let mut network = Network::new(vec![Intcomp::new(vec![99]); 2]);
+        network.connect(0, 2);
+    }
+
+    #[test]
+    #[should_panic(expected = "packets must hold at least an address")]
+    fn empty_packets() {
+        Network::packets(&vec![99], 1, 0);
+    }
+
+    #[test]
+    fn packet_network() {
+        // machine 0 sends (42, 7) to machine 1. Every machine forwards the packets it receives to
+        // address 255 and skips the idle value -1.
+        let program = vec![
+            3, 100, 1005, 100, 11, 104, 1, 104, 42, 104, 7, 3, 101, 1008, 101, -1, 103, 1005, 103,
+            11, 3, 102, 104, 255, 4, 101, 4, 102, 1105, 1, 11,
+        ];
+        let mut network = Network::packets(&program, 3, 3);
+        network.idle_input = Some(-1);
+        assert_eq!(network.run().unwrap(), NetworkState::Idle);
+        assert_eq!(
+            network.outbox.pop_front(),
+            Some(Packet {
+                from: 1,
+                to: 255,
+                data: vec![42, 7],
+            })
+        );
+        assert!(network.outbox.is_empty());
+
+        network.send(2, &[1, 2]);
+        assert_eq!(network.run().unwrap(), NetworkState::Idle);
+        assert_eq!(network.outbox.pop_front().unwrap().data, vec![1, 2]);
+    }
+}
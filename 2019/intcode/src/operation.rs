@@ -0,0 +1,404 @@
+use std::convert::TryFrom;
+use std::fmt;
+
+/// The largest instruction is an opcode followed by three parameters.
+pub(crate) const MAX_OPERATION_SIZE: usize = 4;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Operation {
+    Add {
+        a: i64,
+        b: i64,
+        save: i64,
+        a_mode: Mode,
+        b_mode: Mode,
+        save_mode: Mode,
+    },
+    Mul {
+        a: i64,
+        b: i64,
+        save: i64,
+        a_mode: Mode,
+        b_mode: Mode,
+        save_mode: Mode,
+    },
+    In {
+        save: i64,
+        save_mode: Mode,
+    },
+    Out {
+        read: i64,
+        read_mode: Mode,
+    },
+    JumpIfTrue {
+        check: i64,
+        check_mode: Mode,
+        address: i64,
+        address_mode: Mode,
+    },
+    JumpIfFalse {
+        check: i64,
+        address: i64,
+        check_mode: Mode,
+        address_mode: Mode,
+    },
+    LessThan {
+        a: i64,
+        b: i64,
+        save: i64,
+        a_mode: Mode,
+        b_mode: Mode,
+        save_mode: Mode,
+    },
+    Eql {
+        a: i64,
+        b: i64,
+        save: i64,
+        a_mode: Mode,
+        b_mode: Mode,
+        save_mode: Mode,
+    },
+    AdjustRelativeBase {
+        a: i64,
+        a_mode: Mode,
+    },
+    Exit,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Mode {
+    #[default]
+    Positional,
+    Immeadiate,
+    /// Parameters are offsets from the computer's relative base register.
+    Relative,
+}
+
+impl From<i64> for Mode {
+    fn from(value: i64) -> Self {
+        match value {
+            1 => Mode::Immeadiate,
+            2 => Mode::Relative,
+            _ => Mode::default(),
+        }
+    }
+}
+
+impl From<&i64> for Mode {
+    fn from(value: &i64) -> Self {
+        Mode::from(*value)
+    }
+}
+
+pub(crate) fn digits(number: i64) -> Vec<i64> {
+    let mut divisor = 1;
+    while number >= divisor * 10 {
+        divisor *= 10;
+    }
+
+    let mut digits = Vec::new();
+    let mut n = number;
+    while divisor > 0 {
+        digits.push(n / divisor);
+        n %= divisor;
+        divisor /= 10;
+    }
+    digits
+}
+
+impl TryFrom<&[i64]> for Operation {
+    type Error = String;
+
+    fn try_from(opcode: &[i64]) -> Result<Self, Self::Error> {
+        let operation_code: i64 = *opcode.first().ok_or("empty opcode")?;
+        let mode = |i: u32| Mode::from(operation_code / 10i64.pow(i + 2) % 10);
+        match (operation_code % 100, opcode) {
+            (1, [_raw_op, a, b, save]) => Ok(Operation::Add {
+                a: *a,
+                b: *b,
+                a_mode: mode(0),
+                b_mode: mode(1),
+                save: *save,
+                save_mode: mode(2),
+            }),
+            (2, [_raw_op, a, b, save]) => Ok(Operation::Mul {
+                a: *a,
+                b: *b,
+                a_mode: mode(0),
+                b_mode: mode(1),
+                save: *save,
+                save_mode: mode(2),
+            }),
+            (3, [_raw_op, save]) => Ok(Operation::In {
+                save: *save,
+                save_mode: mode(0),
+            }),
+            (4, [_raw_op, read]) => Ok(Operation::Out {
+                read: *read,
+                read_mode: mode(0),
+            }),
+            (5, [_raw_op, check, address]) => Ok(Operation::JumpIfTrue {
+                check: *check,
+                address: *address,
+                check_mode: mode(0),
+                address_mode: mode(1),
+            }),
+            (6, [_raw_op, check, address]) => Ok(Operation::JumpIfFalse {
+                check: *check,
+                address: *address,
+                check_mode: mode(0),
+                address_mode: mode(1),
+            }),
+            (7, [_raw_op, a, b, save]) => Ok(Operation::LessThan {
+                a: *a,
+                b: *b,
+                a_mode: mode(0),
+                b_mode: mode(1),
+                save: *save,
+                save_mode: mode(2),
+            }),
+            (8, [_raw_op, a, b, save]) => Ok(Operation::Eql {
+                a: *a,
+                b: *b,
+                a_mode: mode(0),
+                b_mode: mode(1),
+                save: *save,
+                save_mode: mode(2),
+            }),
+            (9, [_raw_op, a]) => Ok(Operation::AdjustRelativeBase {
+                a: *a,
+                a_mode: mode(0),
+            }),
+            (99, _) => Ok(Operation::Exit),
+            _ => Err("invalid opcode".into()),
+        }
+    }
+}
+
+impl Operation {
+    /// Splits a raw instruction value into its two digit operation code and the parameter modes.
+    pub fn parse_operation_modes(code: i64) -> (i64, Vec<i64>) {
+        let digits = digits(code);
+        match digits.len() {
+            1 => (digits[0], vec![]),
+            2 => (digits[0] * 10 + digits[1], vec![]),
+            n => (
+                digits[n - 2] * 10 + digits[n - 1],
+                // digits are reversed to preserve flag indexes when the operation has multiple
+                // parameters
+                digits[..n - 2].iter().rev().copied().collect(),
+            ),
+        }
+    }
+
+    /// The number of memory cells taken up by the instruction with the given raw value.
+    pub fn size_by_code(code: i64) -> Result<usize, String> {
+        match code % 100 {
+            1 => Ok(4),
+            2 => Ok(4),
+            3 => Ok(2),
+            4 => Ok(2),
+            5 => Ok(3),
+            6 => Ok(3),
+            7 => Ok(4),
+            8 => Ok(4),
+            9 => Ok(2),
+            99 => Ok(1),
+            _ => Err(format!("invalid code: {}", code)),
+        }
+    }
+
+    pub fn size(&self) -> usize {
+        match self {
+            Self::Add { .. } => 4,
+            Self::Mul { .. } => 4,
+            Self::In { .. } => 2,
+            Self::Out { .. } => 2,
+            Self::JumpIfTrue { .. } => 3,
+            Self::JumpIfFalse { .. } => 3,
+            Self::LessThan { .. } => 4,
+            Self::Eql { .. } => 4,
+            Self::AdjustRelativeBase { .. } => 2,
+            Self::Exit => 1,
+        }
+    }
+
+    /// The short name used for the operation by the disassembler and assembler.
+    pub fn mnemonic(&self) -> &'static str {
+        match self {
+            Self::Add { .. } => "ADD",
+            Self::Mul { .. } => "MUL",
+            Self::In { .. } => "IN",
+            Self::Out { .. } => "OUT",
+            Self::JumpIfTrue { .. } => "JT",
+            Self::JumpIfFalse { .. } => "JF",
+            Self::LessThan { .. } => "LT",
+            Self::Eql { .. } => "EQ",
+            Self::AdjustRelativeBase { .. } => "ARB",
+            Self::Exit => "HLT",
+        }
+    }
+
+    /// The operation's parameters and their modes, in the order they appear in memory.
+    pub fn parameters(&self) -> Vec<(i64, Mode)> {
+        match *self {
+            Self::Add {
+                a,
+                b,
+                save,
+                a_mode,
+                b_mode,
+                save_mode,
+            }
+            | Self::Mul {
+                a,
+                b,
+                save,
+                a_mode,
+                b_mode,
+                save_mode,
+            }
+            | Self::LessThan {
+                a,
+                b,
+                save,
+                a_mode,
+                b_mode,
+                save_mode,
+            }
+            | Self::Eql {
+                a,
+                b,
+                save,
+                a_mode,
+                b_mode,
+                save_mode,
+            } => vec![(a, a_mode), (b, b_mode), (save, save_mode)],
+            Self::In { save, save_mode } => vec![(save, save_mode)],
+            Self::Out { read, read_mode } => vec![(read, read_mode)],
+            Self::JumpIfTrue {
+                check,
+                address,
+                check_mode,
+                address_mode,
+            }
+            | Self::JumpIfFalse {
+                check,
+                address,
+                check_mode,
+                address_mode,
+            } => vec![(check, check_mode), (address, address_mode)],
+            Self::AdjustRelativeBase { a, a_mode } => vec![(a, a_mode)],
+            Self::Exit => vec![],
+        }
+    }
+
+    /// The parameter the operation writes its result to, if it writes to memory.
+    pub fn write_parameter(&self) -> Option<(i64, Mode)> {
+        match *self {
+            Self::Add {
+                save, save_mode, ..
+            }
+            | Self::Mul {
+                save, save_mode, ..
+            }
+            | Self::LessThan {
+                save, save_mode, ..
+            }
+            | Self::Eql {
+                save, save_mode, ..
+            }
+            | Self::In { save, save_mode } => Some((save, save_mode)),
+            _ => None,
+        }
+    }
+}
+
+/// Formats a parameter the way the disassembler shows it: `[x]` for positional, `#x` for
+/// immediate and `rb+x` for relative.
+pub struct Parameter(pub i64, pub Mode);
+
+impl fmt::Display for Parameter {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Parameter(val, Mode::Positional) => write!(f, "[{}]", val),
+            Parameter(val, Mode::Immeadiate) => write!(f, "#{}", val),
+            Parameter(val, Mode::Relative) if *val < 0 => write!(f, "rb{}", val),
+            Parameter(val, Mode::Relative) => write!(f, "rb+{}", val),
+        }
+    }
+}
+
+impl fmt::Display for Operation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.mnemonic())?;
+        for (i, (val, mode)) in self.parameters().into_iter().enumerate() {
+            let sep = if i == 0 { " " } else { ", " };
+            write!(f, "{}{}", sep, Parameter(val, mode))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_digits() {
+        assert_eq!(digits(1002), vec![1, 0, 0, 2]);
+    }
+
+    #[test]
+    fn operation_mode_parsing() {
+        let (operation, modes) = Operation::parse_operation_modes(1002);
+        assert_eq!(operation, 2);
+        assert_eq!(modes, vec![0, 1]);
+
+        let (operation, modes) = Operation::parse_operation_modes(2);
+        assert_eq!(operation, 2);
+        assert_eq!(modes, Vec::<i64>::new());
+
+        let (operation, modes) = Operation::parse_operation_modes(1097);
+        assert_eq!(operation, 97);
+        assert_eq!(modes, vec![0, 1]);
+
+        let (operation, modes) = Operation::parse_operation_modes(21201);
+        assert_eq!(operation, 1);
+        assert_eq!(modes, vec![2, 1, 2]);
+    }
+
+    #[test]
+    fn display() {
+        let operation = Operation::try_from(&[21002, 4, -3, 7][..]).unwrap();
+        assert_eq!(operation.to_string(), "MUL [4], #-3, rb+7");
+        let operation = Operation::try_from(&[204, -1][..]).unwrap();
+        assert_eq!(operation.to_string(), "OUT rb-1");
+        assert_eq!(Operation::Exit.to_string(), "HLT");
+    }
+
+    #[test]
+    fn relative_modes() {
+        assert_eq!(
+            Operation::try_from(&[204, -1][..]),
+            Ok(Operation::Out {
+                read: -1,
+                read_mode: Mode::Relative,
+            })
+        );
+        assert_eq!(
+            Operation::try_from(&[203, 3][..]),
+            Ok(Operation::In {
+                save: 3,
+                save_mode: Mode::Relative,
+            })
+        );
+        assert_eq!(
+            Operation::try_from(&[109, 19][..]),
+            Ok(Operation::AdjustRelativeBase {
+                a: 19,
+                a_mode: Mode::Immeadiate,
+            })
+        );
+    }
+}
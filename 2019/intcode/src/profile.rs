@@ -0,0 +1,310 @@
+//! Execution counts and coverage for Intcode programs.
+//!
+//! Profiling is turned on with `Intcomp::enable_profiling`. Every instruction run with `step` is
+//! then counted by address and by opcode, the memory cells it reads and writes are recorded and
+//! each conditional jump counts how often it was taken. `Profile::report` combines the counts with
+//! a disassembly of the program to list hot loops, code that never ran and branches that only went
+//! one way.
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+
+use crate::disassemble::{disassemble_with_entries, Line};
+use crate::operation::{Mode, Operation};
+
+/// The number of hot loops listed in a report.
+const REPORT_LOOPS: usize = 5;
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Profile {
+    /// Times the instruction at each address was executed.
+    pub executions: BTreeMap<usize, u64>,
+    /// Times each kind of instruction was executed, by mnemonic.
+    pub opcodes: BTreeMap<&'static str, u64>,
+    /// Cells read by an instruction parameter.
+    pub reads: BTreeSet<usize>,
+    /// Cells written by an instruction.
+    pub writes: BTreeSet<usize>,
+    /// Times each conditional jump was taken and not taken.
+    pub branches: BTreeMap<usize, Branch>,
+    /// Times each backward jump was taken, keyed by the jump's address and its target.
+    pub back_edges: BTreeMap<(usize, usize), u64>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Branch {
+    pub taken: u64,
+    pub not_taken: u64,
+}
+
+/// A range of code repeated by a backward jump.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Loop {
+    /// The jump target, where each iteration starts.
+    pub start: usize,
+    /// The address of the jump that repeats the loop.
+    pub end: usize,
+    pub iterations: u64,
+}
+
+/// One executed instruction, as seen by the profiler.
+pub(crate) struct Event<'a> {
+    pub pointer: usize,
+    pub operation: &'a Operation,
+    pub reads: &'a [usize],
+    pub write: Option<usize>,
+    /// Whether a conditional jump was taken. `None` for other instructions.
+    pub taken: Option<bool>,
+    /// Where the instruction pointer moved to.
+    pub next: usize,
+}
+
+impl Profile {
+    pub(crate) fn record(&mut self, event: Event) {
+        *self.executions.entry(event.pointer).or_insert(0) += 1;
+        *self.opcodes.entry(event.operation.mnemonic()).or_insert(0) += 1;
+        self.reads.extend(event.reads);
+        self.writes.extend(event.write);
+        if let Some(taken) = event.taken {
+            let branch = self.branches.entry(event.pointer).or_default();
+            if taken {
+                branch.taken += 1;
+            } else {
+                branch.not_taken += 1;
+            }
+            if taken && event.next <= event.pointer {
+                *self
+                    .back_edges
+                    .entry((event.pointer, event.next))
+                    .or_insert(0) += 1;
+            }
+        }
+    }
+
+    /// Adds the counts from another run, for example to measure the coverage of a test suite
+    /// that runs a program with several inputs.
+    pub fn merge(&mut self, other: &Profile) {
+        for (address, count) in &other.executions {
+            *self.executions.entry(*address).or_insert(0) += count;
+        }
+        for (mnemonic, count) in &other.opcodes {
+            *self.opcodes.entry(mnemonic).or_insert(0) += count;
+        }
+        self.reads.extend(&other.reads);
+        self.writes.extend(&other.writes);
+        for (address, branch) in &other.branches {
+            let total = self.branches.entry(*address).or_default();
+            total.taken += branch.taken;
+            total.not_taken += branch.not_taken;
+        }
+        for (edge, count) in &other.back_edges {
+            *self.back_edges.entry(*edge).or_insert(0) += count;
+        }
+    }
+
+    /// The total number of instructions executed.
+    pub fn instructions(&self) -> u64 {
+        self.executions.values().sum()
+    }
+
+    /// Loops found from taken backward jumps, most iterations first.
+    pub fn hot_loops(&self) -> Vec<Loop> {
+        let mut loops: Vec<Loop> = self
+            .back_edges
+            .iter()
+            .map(|((end, start), iterations)| Loop {
+                start: *start,
+                end: *end,
+                iterations: *iterations,
+            })
+            .collect();
+        loops.sort_by_key(|l| (std::cmp::Reverse(l.iterations), l.start));
+        loops
+    }
+
+    /// Compares the profile with a disassembly of `program`. Executed addresses are used as extra
+    /// entry points so code reached by computed jumps is disassembled too.
+    pub fn report<'a>(&'a self, program: &[i64]) -> Report<'a> {
+        let mut entries = vec![0];
+        entries.extend(self.executions.keys().copied());
+        let disassembly = disassemble_with_entries(program, &entries);
+
+        let mut total = 0;
+        let mut uncovered = Vec::new();
+        let mut partial = Vec::new();
+        for line in disassembly.lines {
+            let (address, operation) = match line {
+                Line::Instruction { address, operation } => (address, operation),
+                Line::Data { .. } => continue,
+            };
+            total += 1;
+            if !self.executions.contains_key(&address) {
+                uncovered.push((address, operation));
+                continue;
+            }
+            // a jump on an immediate check always goes the same way
+            let constant = match operation {
+                Operation::JumpIfTrue { check_mode, .. }
+                | Operation::JumpIfFalse { check_mode, .. } => check_mode == Mode::Immeadiate,
+                _ => false,
+            };
+            if let (Some(branch), false) = (self.branches.get(&address), constant) {
+                if branch.taken == 0 || branch.not_taken == 0 {
+                    partial.push((address, operation, *branch));
+                }
+            }
+        }
+        Report {
+            profile: self,
+            total,
+            uncovered,
+            partial,
+        }
+    }
+}
+
+/// A coverage report built by `Profile::report`. Displays as a text summary.
+#[derive(Debug, Clone)]
+pub struct Report<'a> {
+    profile: &'a Profile,
+    /// The number of instructions found in the program.
+    pub total: usize,
+    /// Instructions that never ran.
+    pub uncovered: Vec<(usize, Operation)>,
+    /// Conditional jumps that ran but always went the same way.
+    pub partial: Vec<(usize, Operation, Branch)>,
+}
+
+impl<'a> Report<'a> {
+    /// The number of instructions that ran at least once.
+    pub fn covered(&self) -> usize {
+        self.total - self.uncovered.len()
+    }
+}
+
+impl<'a> fmt::Display for Report<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let profile = self.profile;
+        writeln!(f, "instructions executed: {}", profile.instructions())?;
+        writeln!(
+            f,
+            "coverage: {}/{} instructions, {} cells read, {} cells written",
+            self.covered(),
+            self.total,
+            profile.reads.len(),
+            profile.writes.len()
+        )?;
+
+        writeln!(f, "\nopcodes:")?;
+        let mut opcodes: Vec<_> = profile.opcodes.iter().collect();
+        opcodes.sort_by_key(|(mnemonic, count)| (std::cmp::Reverse(**count), **mnemonic));
+        for (mnemonic, count) in opcodes {
+            writeln!(f, "  {:<4} {}", mnemonic, count)?;
+        }
+
+        writeln!(f, "\nhot loops:")?;
+        for l in profile.hot_loops().iter().take(REPORT_LOOPS) {
+            writeln!(
+                f,
+                "  {:04}..{:04}  {} iterations",
+                l.start, l.end, l.iterations
+            )?;
+        }
+
+        writeln!(f, "\nuncovered:")?;
+        for (address, operation) in &self.uncovered {
+            writeln!(f, "  {:04}: {}", address, operation)?;
+        }
+
+        writeln!(f, "\npartial branches:")?;
+        for (address, operation, branch) in &self.partial {
+            let way = if branch.taken == 0 {
+                "never taken"
+            } else {
+                "always taken"
+            };
+            writeln!(f, "  {:04}: {}  ; {}", address, operation, way)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assemble::assemble;
+    use crate::intcomp::Intcomp;
+
+    #[test]
+    fn counts_and_loops() {
+        let program = assemble(
+            "
+            start:  out [n]
+                    add [n], #-1, [n]
+                    jt [n], #start
+                    hlt
+            n:      .data 3
+            ",
+        )
+        .unwrap();
+        let mut comp = Intcomp::new(program.clone());
+        comp.enable_profiling();
+        comp.run().unwrap();
+        let profile = comp.profile.as_ref().unwrap();
+
+        assert_eq!(comp.output, vec![3, 2, 1]);
+        assert_eq!(profile.instructions(), 10);
+        assert_eq!(profile.executions[&0], 3);
+        assert_eq!(profile.opcodes["ADD"], 3);
+        assert_eq!(profile.opcodes["HLT"], 1);
+        assert_eq!(profile.reads, [10].iter().copied().collect());
+        assert_eq!(profile.writes, [10].iter().copied().collect());
+        assert_eq!(
+            profile.branches[&6],
+            Branch {
+                taken: 2,
+                not_taken: 1
+            }
+        );
+        assert_eq!(
+            profile.hot_loops(),
+            vec![Loop {
+                start: 0,
+                end: 6,
+                iterations: 2
+            }]
+        );
+        assert_eq!(profile.report(&program).covered(), 4);
+    }
+
+    #[test]
+    fn coverage_of_day_5_compare() {
+        // outputs 999, 1000 or 1001 when the input is below, equal to or above 8
+        let program = vec![
+            3, 21, 1008, 21, 8, 20, 1005, 20, 22, 107, 8, 21, 20, 1006, 20, 31, 1106, 0, 36, 98, 0,
+            0, 1002, 21, 125, 20, 4, 20, 1105, 1, 46, 104, 999, 1105, 1, 46, 1101, 1000, 1, 20, 4,
+            20, 1105, 1, 46, 98, 99,
+        ];
+        let profile = |input: i64| {
+            let mut comp = Intcomp::new(program.clone());
+            comp.input.push_back(input);
+            comp.enable_profiling();
+            comp.run().unwrap();
+            comp.profile.unwrap()
+        };
+
+        let mut total = profile(8);
+        let report = total.report(&program);
+        assert_eq!(report.total, 15);
+        assert_eq!(report.covered(), 7);
+        assert_eq!(report.uncovered[0].0, 9);
+        let partial: Vec<usize> = report.partial.iter().map(|p| p.0).collect();
+        assert_eq!(partial, vec![6]);
+
+        total.merge(&profile(5));
+        total.merge(&profile(300));
+        let report = total.report(&program);
+        assert_eq!(report.covered(), report.total);
+        assert!(report.partial.is_empty());
+        assert!(report.to_string().contains("coverage: 15/15 instructions"));
+    }
+}
@@ -0,0 +1,32 @@
+use std::fs;
+
+pub type Program = Vec<i64>;
+
+/// Parses a comma separated Intcode program. Values that fail to parse are skipped.
+pub fn parse_program(text: &str) -> Program {
+    text.split(',')
+        .map(|s| s.trim().parse())
+        .filter_map(Result::ok)
+        .collect()
+}
+
+/// Loads a comma separated Intcode program from a file.
+pub fn load_program_file(path: &str) -> Program {
+    let text = fs::read_to_string(path).expect("unable to open program file");
+    parse_program(&text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_program_text() {
+        assert_eq!(parse_program("1,0,0,3,99\n"), vec![1, 0, 0, 3, 99]);
+        assert_eq!(parse_program("3, -1 ,4"), vec![3, -1, 4]);
+        assert_eq!(
+            parse_program("104,1125899906842624,99"),
+            vec![104, 1125899906842624, 99]
+        );
+    }
+}
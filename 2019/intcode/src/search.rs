@@ -0,0 +1,158 @@
+//! Searches for program inputs that give a wanted result.
+//!
+//! Some puzzles hand over a program and ask which values, written into a few memory cells before
+//! it starts, make it produce some answer. A `Sweep` patches each candidate set of values into a
+//! copy of a base computer, runs the copies on several threads and keeps every candidate whose
+//! finished computer passes a check.
+use std::ops::Range;
+use std::thread;
+
+use crate::intcomp::{Intcomp, State};
+use crate::io::{Input, Output};
+
+/// A candidate that passed the check, with the computer it finished in.
+#[derive(Debug, Clone)]
+pub struct Match<I, O> {
+    pub values: Vec<i64>,
+    pub comp: Intcomp<I, O>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Sweep<I, O> {
+    base: Intcomp<I, O>,
+    addresses: Vec<usize>,
+    /// The number of threads candidates are spread over.
+    pub threads: usize,
+    /// Candidates still running after this many steps are rejected. Without a limit a candidate
+    /// that loops forever stalls the sweep.
+    pub max_steps: Option<usize>,
+}
+
+impl<I, O> Sweep<I, O>
+where
+    I: Input + Clone + Send + Sync,
+    O: Output + Clone + Send + Sync,
+{
+    /// Creates a sweep that writes each candidate's values to `addresses` of a copy of `base`, in
+    /// order. Uses one thread per available CPU.
+    pub fn new(base: Intcomp<I, O>, addresses: &[usize]) -> Self {
+        Sweep {
+            base,
+            addresses: addresses.to_vec(),
+            threads: thread::available_parallelism().map_or(1, |n| n.get()),
+            max_steps: None,
+        }
+    }
+
+    /// Runs every candidate and returns those where `check` accepts the halted computer, in the
+    /// order the candidates were given. Candidates that fault, wait for input or hit the step
+    /// limit are rejected.
+    pub fn run<C, F>(&self, candidates: C, check: F) -> Vec<Match<I, O>>
+    where
+        C: IntoIterator<Item = Vec<i64>>,
+        F: Fn(&Intcomp<I, O>) -> bool + Sync,
+    {
+        let candidates: Vec<Vec<i64>> = candidates.into_iter().collect();
+        let threads = self.threads.clamp(1, candidates.len().max(1));
+        let check = &check;
+        let candidates = &candidates;
+
+        let mut found: Vec<(usize, Match<I, O>)> = thread::scope(|scope| {
+            let workers: Vec<_> = (0..threads)
+                .map(|worker| {
+                    scope.spawn(move || {
+                        let mut found = Vec::new();
+                        for i in (worker..candidates.len()).step_by(threads) {
+                            if let Some(comp) = self.try_candidate(&candidates[i], check) {
+                                let values = candidates[i].clone();
+                                found.push((i, Match { values, comp }));
+                            }
+                        }
+                        found
+                    })
+                })
+                .collect();
+            workers
+                .into_iter()
+                .flat_map(|worker| worker.join().expect("sweep worker panicked"))
+                .collect()
+        });
+        found.sort_by_key(|(i, _)| *i);
+        found.into_iter().map(|(_, found)| found).collect()
+    }
+
+    fn try_candidate<F>(&self, values: &[i64], check: &F) -> Option<Intcomp<I, O>>
+    where
+        F: Fn(&Intcomp<I, O>) -> bool,
+    {
+        let mut comp = self.base.clone();
+        for (address, val) in self.addresses.iter().zip(values) {
+            comp.set(*address, *val);
+        }
+        let state = match self.max_steps {
+            Some(steps) => comp.run_for(steps),
+            None => comp.run(),
+        };
+        match state {
+            Ok(State::Halted) if check(&comp) => Some(comp),
+            _ => None,
+        }
+    }
+}
+
+/// Every combination of one value from each range, varying the last range fastest.
+pub fn grid(ranges: &[Range<i64>]) -> Vec<Vec<i64>> {
+    ranges.iter().fold(vec![vec![]], |combinations, range| {
+        combinations
+            .iter()
+            .flat_map(|prefix| {
+                range.clone().map(move |val| {
+                    let mut combination = prefix.clone();
+                    combination.push(val);
+                    combination
+                })
+            })
+            .collect()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn grid_order() {
+        assert_eq!(
+            grid(&[0..2, 5..7]),
+            vec![vec![0, 5], vec![0, 6], vec![1, 5], vec![1, 6]]
+        );
+        assert_eq!(grid(&[]), vec![Vec::<i64>::new()]);
+        assert!(grid(&[0..2, 0..0]).is_empty());
+    }
+
+    #[test]
+    fn finds_every_match() {
+        // memory[0] = memory[9] + memory[10]
+        let base = Intcomp::new(vec![1, 9, 10, 0, 99, 0, 0, 0, 0, 0, 0]);
+        let mut sweep = Sweep::new(base, &[9, 10]);
+        sweep.threads = 3;
+        let found = sweep.run(grid(&[0..10, 0..10]), |comp| comp.memory[0] == 4);
+        let values: Vec<Vec<i64>> = found.iter().map(|m| m.values.clone()).collect();
+        assert_eq!(
+            values,
+            vec![vec![0, 4], vec![1, 3], vec![2, 2], vec![3, 1], vec![4, 0]]
+        );
+        assert_eq!(found[1].comp.memory.as_slice()[9..], [1, 3]);
+    }
+
+    #[test]
+    fn rejects_stuck_candidates() {
+        // jumps back to itself forever when memory[6] is non-zero, otherwise halts
+        let base = Intcomp::new(vec![1005, 6, 0, 99, 0, 0, 0]);
+        let mut sweep = Sweep::new(base, &[6]);
+        sweep.max_steps = Some(100);
+        let found = sweep.run((0..3).map(|val| vec![val]), |_| true);
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].values, vec![0]);
+    }
+}
@@ -0,0 +1,376 @@
+//! A small language for password policies.
+//!
+//! A rule is written `<kind> <numbers> <letter>`:
+//!
+//! - `count 1-3 a` needs between 1 and 3 `a`s in the password.
+//! - `exactly-one 1,3 a` needs an `a` at exactly one of positions 1 and 3.
+//! - `all 1,3 a` needs an `a` at both positions 1 and 3.
+//!
+//! Positions start from 1. A pair of positions can also be written as a range, `1-3`, which means
+//! positions 1 and 3 rather than every position between them. That is how the puzzle input writes
+//! them, so an input line `1-3 a: abcde` is a rule with its kind left out, and each part of the
+//! puzzle is just a different `Kind` for the same lines. A rule uses either `-` or `,`, never both,
+//! and a count only uses `-`. Positions past the end of a password never hold the letter.
+use std::fmt;
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Kind {
+    Count,
+    ExactlyOne,
+    All,
+}
+
+impl FromStr for Kind {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, ParseError> {
+        match s {
+            "count" => Ok(Kind::Count),
+            "exactly-one" => Ok(Kind::ExactlyOne),
+            "all" => Ok(Kind::All),
+            _ => Err(ParseError(format!("unknown policy kind {:?}", s))),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError(pub String);
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Rule {
+    Count {
+        letter: char,
+        min: usize,
+        max: usize,
+    },
+    ExactlyOne {
+        letter: char,
+        positions: Vec<usize>,
+    },
+    All {
+        letter: char,
+        positions: Vec<usize>,
+    },
+}
+
+impl Rule {
+    /// Parses a rule without its kind, such as `1-3 a`.
+    pub fn parse_with_kind(kind: Kind, spec: &str) -> Result<Self, ParseError> {
+        let mut words = spec.split_whitespace();
+        let (numbers, letter) = match (words.next(), words.next(), words.next()) {
+            (Some(numbers), Some(letter), None) => (numbers, letter),
+            _ => {
+                return Err(ParseError(format!(
+                    "expected numbers and a letter in {:?}",
+                    spec
+                )))
+            }
+        };
+        let mut chars = letter.chars();
+        let letter = match (chars.next(), chars.next()) {
+            (Some(c), None) => c,
+            _ => return Err(ParseError(format!("{:?} is not a single letter", letter))),
+        };
+        let number = |n: &str| {
+            n.parse::<usize>()
+                .map_err(|_| ParseError(format!("{:?} is not a number", n)))
+        };
+        match kind {
+            Kind::Count => match numbers.split_once('-') {
+                Some((min, max)) => {
+                    let (min, max) = (number(min)?, number(max)?);
+                    if min > max {
+                        return Err(ParseError(format!("{:?} is not a count range", spec)));
+                    }
+                    Ok(Rule::Count { letter, min, max })
+                }
+                None => Err(ParseError(format!("{:?} is not a count range", spec))),
+            },
+            Kind::ExactlyOne | Kind::All => {
+                let positions: Vec<usize> = match numbers.split_once('-') {
+                    Some((first, second)) => vec![number(first)?, number(second)?],
+                    None => numbers.split(',').map(number).collect::<Result<_, _>>()?,
+                };
+                if positions.contains(&0) {
+                    return Err(ParseError("positions start from 1".into()));
+                }
+                Ok(match kind {
+                    Kind::ExactlyOne => Rule::ExactlyOne { letter, positions },
+                    _ => Rule::All { letter, positions },
+                })
+            }
+        }
+    }
+
+    /// Checks a password, explaining why it failed.
+    pub fn check(&self, password: &str) -> Result<(), Failure> {
+        let letters: Vec<char> = password.chars().collect();
+        // there is no position 0, so it never holds the letter
+        let holds = |letter: char, position: &usize| {
+            position.checked_sub(1).and_then(|i| letters.get(i)) == Some(&letter)
+        };
+        match self {
+            Rule::Count { letter, min, max } => {
+                let found = letters.iter().filter(|c| *c == letter).count();
+                if (*min..=*max).contains(&found) {
+                    Ok(())
+                } else {
+                    Err(Failure::Count {
+                        letter: *letter,
+                        found,
+                        min: *min,
+                        max: *max,
+                    })
+                }
+            }
+            Rule::ExactlyOne { letter, positions } => {
+                let matched: Vec<usize> = positions
+                    .iter()
+                    .filter(|p| holds(*letter, p))
+                    .copied()
+                    .collect();
+                if matched.len() == 1 {
+                    Ok(())
+                } else {
+                    Err(Failure::NotExactlyOne {
+                        letter: *letter,
+                        matched,
+                    })
+                }
+            }
+            Rule::All { letter, positions } => {
+                let missing: Vec<usize> = positions
+                    .iter()
+                    .filter(|p| !holds(*letter, p))
+                    .copied()
+                    .collect();
+                if missing.is_empty() {
+                    Ok(())
+                } else {
+                    Err(Failure::Missing {
+                        letter: *letter,
+                        missing,
+                        len: letters.len(),
+                    })
+                }
+            }
+        }
+    }
+}
+
+impl FromStr for Rule {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, ParseError> {
+        let s = s.trim();
+        let (kind, spec) = s
+            .split_once(' ')
+            .ok_or_else(|| ParseError(format!("expected a policy kind in {:?}", s)))?;
+        Rule::parse_with_kind(kind.parse()?, spec)
+    }
+}
+
+/// Why a password broke a rule.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Failure {
+    Count {
+        letter: char,
+        found: usize,
+        min: usize,
+        max: usize,
+    },
+    /// The positions that held the letter, when exactly one should have.
+    NotExactlyOne { letter: char, matched: Vec<usize> },
+    /// The positions that didn't hold the letter, for a password `len` letters long.
+    Missing {
+        letter: char,
+        missing: Vec<usize>,
+        len: usize,
+    },
+}
+
+fn list(positions: &[usize]) -> String {
+    let numbers: Vec<String> = positions.iter().map(usize::to_string).collect();
+    match positions.len() {
+        1 => format!("position {}", numbers[0]),
+        _ => format!("positions {}", numbers.join(", ")),
+    }
+}
+
+impl fmt::Display for Failure {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Failure::Count {
+                letter,
+                found,
+                min,
+                max,
+            } => write!(
+                f,
+                "{:?} appears {} times, expected {} to {}",
+                letter, found, min, max
+            ),
+            Failure::NotExactlyOne { letter, matched } if matched.is_empty() => {
+                write!(f, "{:?} is at none of the positions", letter)
+            }
+            Failure::NotExactlyOne { letter, matched } => write!(
+                f,
+                "{:?} is at {}, expected exactly one",
+                letter,
+                list(matched)
+            ),
+            Failure::Missing {
+                letter,
+                missing,
+                len,
+            } => {
+                write!(f, "{:?} is not at {}", letter, list(missing))?;
+                if missing.iter().any(|p| p > len) {
+                    write!(f, " (the password has {} letters)", len)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+/// A line of the puzzle input: a rule without its kind, then the password.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Entry {
+    pub rule: Rule,
+    pub password: String,
+}
+
+impl Entry {
+    /// Parses a line such as `1-3 a: abcde`, reading the rule as `kind`. The password can be
+    /// empty.
+    pub fn parse(kind: Kind, line: &str) -> Result<Self, ParseError> {
+        let (spec, password) = line
+            .split_once(':')
+            .ok_or_else(|| ParseError(format!("expected `<rule>: <password>` in {:?}", line)))?;
+        Ok(Entry {
+            rule: Rule::parse_with_kind(kind, spec)?,
+            password: password.trim().into(),
+        })
+    }
+
+    pub fn check(&self) -> Result<(), Failure> {
+        self.rule.check(&self.password)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: [&str; 3] = ["1-3 a: abcde", "1-3 b: cdefg", "2-9 c: ccccccccc"];
+
+    fn valid(kind: Kind) -> Vec<bool> {
+        EXAMPLE
+            .iter()
+            .map(|line| Entry::parse(kind, line).unwrap().check().is_ok())
+            .collect()
+    }
+
+    #[test]
+    fn both_parts() {
+        assert_eq!(valid(Kind::Count), vec![true, false, true]);
+        assert_eq!(valid(Kind::ExactlyOne), vec![true, false, false]);
+        assert_eq!(valid(Kind::All), vec![false, false, true]);
+    }
+
+    #[test]
+    fn rule_syntax() {
+        assert_eq!(
+            "all 1,4,5 z".parse(),
+            Ok(Rule::All {
+                letter: 'z',
+                positions: vec![1, 4, 5]
+            })
+        );
+        assert_eq!(
+            "count 2-6 c".parse(),
+            Ok(Rule::Count {
+                letter: 'c',
+                min: 2,
+                max: 6
+            })
+        );
+        assert!("count 6-2 c".parse::<Rule>().is_err());
+        assert_eq!(
+            "exactly-one 1-3 c".parse(),
+            Ok(Rule::ExactlyOne {
+                letter: 'c',
+                positions: vec![1, 3]
+            })
+        );
+        assert!("count 1,2,3 c".parse::<Rule>().is_err());
+        assert!("count 1,3 c".parse::<Rule>().is_err());
+        assert!("count 1-2,3 c".parse::<Rule>().is_err());
+        assert!("all 1-2-3 c".parse::<Rule>().is_err());
+        assert!("all 1-2,3 c".parse::<Rule>().is_err());
+        assert!("all 1,2-3 c".parse::<Rule>().is_err());
+        assert!("all 0,1 c".parse::<Rule>().is_err());
+        assert!("some 1-2 c".parse::<Rule>().is_err());
+        assert!("all 1-2 cc".parse::<Rule>().is_err());
+        assert!(Entry::parse(Kind::All, "1-2 c cc").is_err());
+        assert_eq!(
+            Entry::parse(Kind::Count, "1-3 a: ".trim()),
+            Ok(Entry {
+                rule: Rule::Count {
+                    letter: 'a',
+                    min: 1,
+                    max: 3
+                },
+                password: String::new()
+            })
+        );
+    }
+
+    #[test]
+    fn explanations() {
+        let explain = |rule: &str, password: &str| {
+            let rule: Rule = rule.parse().unwrap();
+            rule.check(password).unwrap_err().to_string()
+        };
+        assert_eq!(
+            explain("count 1-3 b", "cdefg"),
+            "'b' appears 0 times, expected 1 to 3"
+        );
+        assert_eq!(
+            explain("exactly-one 2-9 c", "ccccccccc"),
+            "'c' is at positions 2, 9, expected exactly one"
+        );
+        assert_eq!(
+            explain("exactly-one 1,2 c", "ab"),
+            "'c' is at none of the positions"
+        );
+        assert_eq!(
+            explain("all 1,9 a", "abc"),
+            "'a' is not at position 9 (the password has 3 letters)"
+        );
+        // a position past the end doesn't panic, it just doesn't match
+        assert_eq!(
+            "exactly-one 1,20 a".parse::<Rule>().unwrap().check("abc"),
+            Ok(())
+        );
+        // neither does position 0 in a rule built without the parser
+        let rule = Rule::All {
+            letter: 'a',
+            positions: vec![0, 1],
+        };
+        assert_eq!(
+            rule.check("abc").unwrap_err().to_string(),
+            "'a' is not at position 0"
+        );
+    }
+}
@@ -1,68 +1,36 @@
 use std::{env, fs};
-use std::io::BufReader;
-use std::io::prelude::*;
 
-#[derive(PartialEq)]
+mod slope;
+
+use slope::{Edges, Grid};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 enum Cell {
     Tree,
     Empty,
 }
 
-impl From<char> for Cell {
-    fn from(c: char) -> Self {
-        match c {
-            '.' => Cell::Empty,
-            '#' => Cell::Tree,
-            _ => panic!("unknown map element {}", c),
-        }
-    }
-}
-
-struct Map {
-    cells: Vec<Vec<Cell>>,
-}
-
-impl Map {
-    fn from_file(path: &str) -> Result<Self, std::io::Error> {
-        let mut cells = Vec::new();
-        let file = fs::File::open(path)?;
-        let reader = BufReader::new(file);
-        for line in reader.lines() {
-            let line = line?;
-            cells.push(line.trim().chars()
-                .map(Cell::from)
-                .collect()
-            )
-        }
-        Ok(Map { cells })
-    }
+impl TryFrom<char> for Cell {
+    type Error = char;
 
-    fn width(&self) -> usize {
-        self.cells[0].len()
-    }
-
-    /// Returns the number of trees encountered with the given traversal trajectory
-    fn traverse(&self, right: isize, down: isize) -> usize {
-        let mut posx: isize = 0;
-        let width = self.width() as isize;
-        let mut trees = 0;
-        for (_, lateral) in self.cells.iter().enumerate().filter(|(i, _)| i % down as usize == 0) {
-            if lateral[posx as usize] == Cell::Tree {
-                trees += 1;
-            }
-            posx = if posx + right >= 0 {
-                (posx + right) % width
-            } else {
-                width - ((posx + right) % width)
-            }
+    fn try_from(c: char) -> Result<Self, char> {
+        match c {
+            '.' => Ok(Cell::Empty),
+            '#' => Ok(Cell::Tree),
+            _ => Err(c),
         }
-        trees
     }
 }
 
-fn main() {
+fn main() -> anyhow::Result<()> {
     let path = env::args().nth(1).expect("Please provide an input file argument.");
-    let map = Map::from_file(&path).expect("Could not read map file");
+    let edges = match env::args().nth(2).as_deref() {
+        None | Some("horizontal") => Edges::Horizontal,
+        Some("toroidal") => Edges::Toroidal,
+        Some("clamped") => Edges::Clamped,
+        Some(other) => anyhow::bail!("unknown edge mode {:?}", other),
+    };
+    let map: Grid<Cell> = Grid::parse(&fs::read_to_string(&path)?)?;
     let traversals = [
         (1, 1),
         (3, 1),
@@ -70,9 +38,11 @@ fn main() {
         (7, 1),
         (1, 2),
     ];
-    let trees_product = traversals
-        .into_iter()
-        .map(|(r, d)| map.traverse(r, d))
-        .reduce(std::ops::Mul::mul).unwrap();
+    let trees_product: usize = map
+        .walk_all(&traversals, edges)
+        .iter()
+        .map(|walk| walk.count(&Cell::Tree))
+        .product();
     println!("{} trees product", trees_product);
+    Ok(())
 }
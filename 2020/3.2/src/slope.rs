@@ -0,0 +1,294 @@
+//! Walks straight lines across a grid of cells.
+//!
+//! A walk starts in the top left corner and moves `(dx, dy)` each step, with `y` growing downward.
+//! What happens at the grid's edges depends on the `Edges` mode. Every walk ends at the latest
+//! when it gets back to a position it has already visited, so slopes that never leave the grid,
+//! such as `(1, 0)`, still finish.
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::hash::Hash;
+
+pub type Position = (usize, usize);
+
+/// A character in a map that isn't a known cell, a row that's a different width from the first or
+/// a blank line between rows.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseError {
+    UnknownCell {
+        line: usize,
+        column: usize,
+        found: char,
+    },
+    Ragged {
+        line: usize,
+        width: usize,
+        expected: usize,
+    },
+    BlankLine {
+        line: usize,
+    },
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseError::UnknownCell {
+                line,
+                column,
+                found,
+            } => {
+                write!(
+                    f,
+                    "line {}, column {}: unknown map element {:?}",
+                    line, column, found
+                )
+            }
+            ParseError::Ragged {
+                line,
+                width,
+                expected,
+            } => {
+                write!(
+                    f,
+                    "line {}: {} cells wide, expected {}",
+                    line, width, expected
+                )
+            }
+            ParseError::BlankLine { line } => {
+                write!(f, "line {}: blank line inside the map", line)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// What happens when a walk crosses an edge of the grid.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Edges {
+    /// The grid repeats to the left and right. The walk ends when it leaves the top or bottom.
+    Horizontal,
+    /// The grid repeats in every direction, so the walk only ends when it gets back to a
+    /// position it visited.
+    Toroidal,
+    /// The walk stops at the left and right edges and keeps sliding along them. It ends when it
+    /// leaves the top or bottom.
+    Clamped,
+}
+
+/// The cells a walk visited, in order, starting with the top left corner.
+#[derive(Debug, Clone)]
+pub struct Walk<C> {
+    pub path: Vec<Position>,
+    pub counts: HashMap<C, usize>,
+}
+
+impl<C: Eq + Hash> Walk<C> {
+    /// The number of times the walk landed on a kind of cell.
+    pub fn count(&self, cell: &C) -> usize {
+        self.counts.get(cell).copied().unwrap_or(0)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Grid<C> {
+    rows: Vec<Vec<C>>,
+}
+
+impl<C> Grid<C>
+where
+    C: TryFrom<char> + Copy + Eq + Hash,
+{
+    /// Parses a grid with a row per line. Lines can end with `\r\n`, and blank lines before and
+    /// after the grid are ignored.
+    pub fn parse(text: &str) -> Result<Self, ParseError> {
+        let mut rows: Vec<Vec<C>> = Vec::new();
+        let mut blank = None;
+        for (i, line) in text.lines().enumerate() {
+            let line = line.strip_suffix('\r').unwrap_or(line);
+            if line.is_empty() {
+                if !rows.is_empty() && blank.is_none() {
+                    blank = Some(i + 1);
+                }
+                continue;
+            }
+            if let Some(line) = blank {
+                return Err(ParseError::BlankLine { line });
+            }
+            let row = line
+                .chars()
+                .enumerate()
+                .map(|(column, c)| {
+                    C::try_from(c).map_err(|_| ParseError::UnknownCell {
+                        line: i + 1,
+                        column: column + 1,
+                        found: c,
+                    })
+                })
+                .collect::<Result<Vec<C>, _>>()?;
+            if let Some(first) = rows.first() {
+                if row.len() != first.len() {
+                    return Err(ParseError::Ragged {
+                        line: i + 1,
+                        width: row.len(),
+                        expected: first.len(),
+                    });
+                }
+            }
+            rows.push(row);
+        }
+        Ok(Grid { rows })
+    }
+
+    pub fn width(&self) -> usize {
+        self.rows.first().map_or(0, Vec::len)
+    }
+
+    pub fn height(&self) -> usize {
+        self.rows.len()
+    }
+
+    pub fn get(&self, (x, y): Position) -> C {
+        self.rows[y][x]
+    }
+
+    /// Walks from the top left corner along `(dx, dy)`.
+    pub fn walk(&self, (dx, dy): (isize, isize), edges: Edges) -> Walk<C> {
+        let mut walk = Walk {
+            path: Vec::new(),
+            counts: HashMap::new(),
+        };
+        let (width, height) = (self.width() as isize, self.height() as isize);
+        if width == 0 {
+            return walk;
+        }
+        let mut visited = HashSet::new();
+        let (mut x, mut y) = (0isize, 0isize);
+        while visited.insert((x, y)) {
+            let position = (x as usize, y as usize);
+            walk.path.push(position);
+            *walk.counts.entry(self.get(position)).or_insert(0) += 1;
+            x = match edges {
+                Edges::Clamped => (x + dx).clamp(0, width - 1),
+                Edges::Horizontal | Edges::Toroidal => (x + dx).rem_euclid(width),
+            };
+            y = match edges {
+                Edges::Toroidal => (y + dy).rem_euclid(height),
+                Edges::Horizontal | Edges::Clamped => y + dy,
+            };
+            if !(0..height).contains(&y) {
+                break;
+            }
+        }
+        walk
+    }
+
+    /// Walks every slope, giving the walks in the same order.
+    pub fn walk_all(&self, slopes: &[(isize, isize)], edges: Edges) -> Vec<Walk<C>> {
+        slopes
+            .iter()
+            .map(|slope| self.walk(*slope, edges))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    enum Cell {
+        Tree,
+        Empty,
+    }
+
+    impl TryFrom<char> for Cell {
+        type Error = char;
+
+        fn try_from(c: char) -> Result<Self, char> {
+            match c {
+                '.' => Ok(Cell::Empty),
+                '#' => Ok(Cell::Tree),
+                _ => Err(c),
+            }
+        }
+    }
+
+    const EXAMPLE: &str = "
+..##.......
+#...#...#..
+.#....#..#.
+..#.#...#.#
+.#...##..#.
+..#.##.....
+.#.#.#....#
+.#........#
+#.##...#...
+#...##....#
+.#..#...#.#
+";
+
+    #[test]
+    fn example_slopes() {
+        let grid: Grid<Cell> = Grid::parse(EXAMPLE).unwrap();
+        let trees: Vec<usize> = grid
+            .walk_all(&[(1, 1), (3, 1), (5, 1), (7, 1), (1, 2)], Edges::Horizontal)
+            .iter()
+            .map(|walk| walk.count(&Cell::Tree))
+            .collect();
+        assert_eq!(trees, vec![2, 7, 3, 4, 2]);
+
+        let walk = grid.walk((3, 1), Edges::Horizontal);
+        assert_eq!(walk.path.len(), 11);
+        assert_eq!(walk.path[4], (1, 4));
+        assert_eq!(walk.count(&Cell::Tree) + walk.count(&Cell::Empty), 11);
+    }
+
+    #[test]
+    fn edges() {
+        let grid: Grid<Cell> = Grid::parse("...\n.#.\n").unwrap();
+        assert_eq!(
+            grid.walk((-1, 1), Edges::Horizontal).path,
+            vec![(0, 0), (2, 1)]
+        );
+        assert_eq!(grid.walk((5, 1), Edges::Clamped).path, vec![(0, 0), (2, 1)]);
+        assert_eq!(grid.walk((1, 0), Edges::Clamped).path.len(), 3);
+        let torus = grid.walk((1, 1), Edges::Toroidal);
+        assert_eq!(torus.path.len(), 6);
+        assert_eq!(torus.count(&Cell::Tree), 1);
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert_eq!(
+            Grid::<Cell>::parse("..\n.x\n"),
+            Err(ParseError::UnknownCell {
+                line: 2,
+                column: 2,
+                found: 'x'
+            })
+        );
+        assert_eq!(
+            Grid::<Cell>::parse("..\n...\n"),
+            Err(ParseError::Ragged {
+                line: 2,
+                width: 3,
+                expected: 2
+            })
+        );
+        assert_eq!(
+            Grid::<Cell>::parse("..\n.#\n\n..\n"),
+            Err(ParseError::BlankLine { line: 3 })
+        );
+        assert_eq!(
+            Grid::<Cell>::parse(".. \n.#\n"),
+            Err(ParseError::UnknownCell {
+                line: 1,
+                column: 3,
+                found: ' '
+            })
+        );
+        let grid: Grid<Cell> = Grid::parse("\r\n..\r\n.#\r\n\r\n").unwrap();
+        assert_eq!((grid.width(), grid.height()), (2, 2));
+    }
+}
@@ -0,0 +1,249 @@
+//! Declarative validation for records made of `key:value` fields.
+//!
+//! A `Schema` lists the fields a record may have, whether each one is required and a `Rule` its
+//! value must follow. Checking a record gives every problem found rather than stopping at the
+//! first one, so a report can say why a record was rejected.
+use std::collections::HashMap;
+use std::fmt;
+use std::ops::RangeInclusive;
+
+/// What a field's value must look like.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Rule {
+    /// Anything goes.
+    Any,
+    /// A number within a range, such as a year.
+    Year(RangeInclusive<u32>),
+    /// A number followed by one of the units, each with its own range. For example `183cm`.
+    Measure(Vec<(&'static str, RangeInclusive<u32>)>),
+    /// `#` followed by six lowercase hex digits.
+    HexColor,
+    /// One of a fixed set of words.
+    OneOf(Vec<&'static str>),
+    /// Exactly this many decimal digits. Leading zeros count.
+    Digits(usize),
+}
+
+/// Why a field failed its rule.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Problem {
+    Missing,
+    NotANumber(String),
+    OutOfRange {
+        value: u32,
+        range: RangeInclusive<u32>,
+    },
+    UnknownUnit(String),
+    NotHexColor(String),
+    NotOneOf(String),
+    WrongDigits {
+        value: String,
+        expected: usize,
+    },
+}
+
+impl fmt::Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Problem::Missing => write!(f, "missing"),
+            Problem::NotANumber(value) => write!(f, "{:?} is not a number", value),
+            Problem::OutOfRange { value, range } => write!(
+                f,
+                "{} is not between {} and {}",
+                value,
+                range.start(),
+                range.end()
+            ),
+            Problem::UnknownUnit(value) => write!(f, "{:?} does not have a known unit", value),
+            Problem::NotHexColor(value) => write!(f, "{:?} is not a hex color", value),
+            Problem::NotOneOf(value) => write!(f, "{:?} is not an allowed value", value),
+            Problem::WrongDigits { value, expected } => {
+                write!(f, "{:?} is not {} digits", value, expected)
+            }
+        }
+    }
+}
+
+impl Rule {
+    pub fn check(&self, value: &str) -> Result<(), Problem> {
+        match self {
+            Rule::Any => Ok(()),
+            Rule::Year(range) => check_number(value, range),
+            Rule::Measure(units) => {
+                let (scalar, range) = units
+                    .iter()
+                    .find_map(|(unit, range)| value.strip_suffix(unit).map(|s| (s, range)))
+                    .ok_or_else(|| Problem::UnknownUnit(value.into()))?;
+                check_number(scalar, range)
+            }
+            Rule::HexColor => match value.strip_prefix('#') {
+                Some(hex)
+                    if hex.len() == 6
+                        && hex.chars().all(|c| matches!(c, '0'..='9' | 'a'..='f')) =>
+                {
+                    Ok(())
+                }
+                _ => Err(Problem::NotHexColor(value.into())),
+            },
+            Rule::OneOf(words) => {
+                if words.contains(&value) {
+                    Ok(())
+                } else {
+                    Err(Problem::NotOneOf(value.into()))
+                }
+            }
+            Rule::Digits(expected) => {
+                if value.len() == *expected && value.chars().all(|c| c.is_ascii_digit()) {
+                    Ok(())
+                } else {
+                    Err(Problem::WrongDigits {
+                        value: value.into(),
+                        expected: *expected,
+                    })
+                }
+            }
+        }
+    }
+}
+
+fn check_number(value: &str, range: &RangeInclusive<u32>) -> Result<(), Problem> {
+    let number: u32 = value
+        .parse()
+        .map_err(|_| Problem::NotANumber(value.into()))?;
+    if range.contains(&number) {
+        Ok(())
+    } else {
+        Err(Problem::OutOfRange {
+            value: number,
+            range: range.clone(),
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Field {
+    pub name: &'static str,
+    pub required: bool,
+    pub rule: Rule,
+}
+
+/// A field that failed, and why.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldError {
+    pub field: &'static str,
+    pub problem: Problem,
+}
+
+impl fmt::Display for FieldError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}: {}", self.field, self.problem)
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Schema {
+    pub fields: Vec<Field>,
+}
+
+impl Schema {
+    pub fn new() -> Self {
+        Schema::default()
+    }
+
+    pub fn required(mut self, name: &'static str, rule: Rule) -> Self {
+        self.fields.push(Field {
+            name,
+            required: true,
+            rule,
+        });
+        self
+    }
+
+    /// A field that is checked when it is present. Fields the schema doesn't list are ignored,
+    /// so an optional `Rule::Any` field only documents that it exists.
+    pub fn optional(mut self, name: &'static str, rule: Rule) -> Self {
+        self.fields.push(Field {
+            name,
+            required: false,
+            rule,
+        });
+        self
+    }
+
+    /// Every field of `values` that breaks the schema, in schema order.
+    pub fn check(&self, values: &HashMap<String, String>) -> Vec<FieldError> {
+        self.fields
+            .iter()
+            .filter_map(|field| {
+                let problem = match values.get(field.name) {
+                    Some(value) => field.rule.check(value).err()?,
+                    None if field.required => Problem::Missing,
+                    None => return None,
+                };
+                Some(FieldError {
+                    field: field.name,
+                    problem,
+                })
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(text: &str) -> HashMap<String, String> {
+        text.split_whitespace()
+            .filter_map(|pair| pair.split_once(':'))
+            .map(|(key, val)| (key.into(), val.into()))
+            .collect()
+    }
+
+    #[test]
+    fn rules() {
+        let height = Rule::Measure(vec![("cm", 150..=193), ("in", 59..=76)]);
+        assert_eq!(height.check("60in"), Ok(()));
+        assert_eq!(height.check("190cm"), Ok(()));
+        assert_eq!(
+            height.check("190in"),
+            Err(Problem::OutOfRange {
+                value: 190,
+                range: 59..=76
+            })
+        );
+        assert_eq!(height.check("190"), Err(Problem::UnknownUnit("190".into())));
+        assert_eq!(Rule::HexColor.check("#123abc"), Ok(()));
+        assert!(Rule::HexColor.check("#123abz").is_err());
+        assert!(Rule::HexColor.check("123abc").is_err());
+        assert_eq!(Rule::Digits(9).check("000000001"), Ok(()));
+        assert!(Rule::Digits(9).check("0123456789").is_err());
+        assert!(Rule::OneOf(vec!["brn"]).check("wat").is_err());
+        assert_eq!(
+            Rule::Year(1920..=2002).check("20o2"),
+            Err(Problem::NotANumber("20o2".into()))
+        );
+    }
+
+    #[test]
+    fn report() {
+        let schema = Schema::new()
+            .required("byr", Rule::Year(1920..=2002))
+            .required("pid", Rule::Digits(9))
+            .optional("hcl", Rule::HexColor)
+            .optional("cid", Rule::Any);
+        assert!(schema
+            .check(&record("byr:1980 pid:012345678 cid:x"))
+            .is_empty());
+        let errors = schema.check(&record("byr:2003 hcl:z other:1"));
+        let messages: Vec<String> = errors.iter().map(ToString::to_string).collect();
+        assert_eq!(
+            messages,
+            vec![
+                "byr: 2003 is not between 1920 and 2002",
+                "pid: missing",
+                "hcl: \"z\" is not a hex color",
+            ]
+        );
+    }
+}
@@ -0,0 +1,349 @@
+//! Decodes binary space partitioned seat codes.
+//!
+//! A code narrows a plane down to one seat: each letter picks the lower or upper half of the
+//! remaining rows, then of the remaining columns. A `Plane` describes the letters used for each
+//! axis and how many of them there are, so the same decoder works for any power of two sized
+//! plane. `SeatMap` checks a list of seats against the plane, finding empty and double booked seats,
+//! and draws it.
+use std::fmt;
+
+/// One axis of a plane, split `bits` times. `low` picks the lower half and `high` the upper.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Axis {
+    low: char,
+    high: char,
+    bits: u32,
+}
+
+impl Axis {
+    /// An axis whose size fits in a `usize`, picked with two different letters.
+    pub fn new(low: char, high: char, bits: u32) -> Result<Self, PlaneError> {
+        if low == high {
+            return Err(PlaneError::SameLetter(low));
+        }
+        if bits >= usize::BITS {
+            return Err(PlaneError::TooWide { bits });
+        }
+        Ok(Axis { low, high, bits })
+    }
+
+    pub fn size(&self) -> usize {
+        1 << self.bits
+    }
+
+    fn decode(&self, letters: &[char], offset: usize) -> Result<usize, DecodeError> {
+        letters.iter().enumerate().try_fold(0, |val, (i, &c)| {
+            let bit = match c {
+                c if c == self.low => 0,
+                c if c == self.high => 1,
+                _ => {
+                    return Err(DecodeError::Letter {
+                        position: offset + i + 1,
+                        found: c,
+                        expected: (self.low, self.high),
+                    })
+                }
+            };
+            Ok(val << 1 | bit)
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DecodeError {
+    Length {
+        expected: usize,
+        found: usize,
+    },
+    Letter {
+        position: usize,
+        found: char,
+        expected: (char, char),
+    },
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DecodeError::Length { expected, found } => {
+                write!(f, "expected {} letters, found {}", expected, found)
+            }
+            DecodeError::Letter {
+                position,
+                found,
+                expected,
+            } => write!(
+                f,
+                "letter {} is {:?}, expected {:?} or {:?}",
+                position, found, expected.0, expected.1
+            ),
+        }
+    }
+}
+
+impl std::error::Error for DecodeError {}
+
+/// An axis or plane that can't be built, or a seat that isn't on a plane.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PlaneError {
+    /// Both halves of an axis use the same letter.
+    SameLetter(char),
+    /// The plane would have too many seats to number with a `usize`.
+    TooWide {
+        bits: u32,
+    },
+    OffPlane(Seat),
+}
+
+impl fmt::Display for PlaneError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PlaneError::SameLetter(letter) => {
+                write!(f, "{:?} picks both halves of an axis", letter)
+            }
+            PlaneError::TooWide { bits } => write!(f, "{} letters is too many to number", bits),
+            PlaneError::OffPlane(seat) => {
+                write!(
+                    f,
+                    "row {}, column {} is not on the plane",
+                    seat.row, seat.col
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for PlaneError {}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Seat {
+    pub row: usize,
+    pub col: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Plane {
+    rows: Axis,
+    cols: Axis,
+}
+
+impl Plane {
+    /// A plane whose seats can all be numbered with a `usize`.
+    pub fn new(rows: Axis, cols: Axis) -> Result<Self, PlaneError> {
+        let bits = rows.bits + cols.bits;
+        if bits >= usize::BITS {
+            return Err(PlaneError::TooWide { bits });
+        }
+        Ok(Plane { rows, cols })
+    }
+
+    /// The puzzle's plane: 128 rows picked with `F`ront and `B`ack, then 8 columns picked with
+    /// `L`eft and `R`ight.
+    pub fn airline() -> Self {
+        let rows = Axis::new('F', 'B', 7).expect("valid rows");
+        let cols = Axis::new('L', 'R', 3).expect("valid columns");
+        Plane::new(rows, cols).expect("valid plane")
+    }
+
+    pub fn decode(&self, code: &str) -> Result<Seat, DecodeError> {
+        let split = self.rows.bits as usize;
+        let expected = split + self.cols.bits as usize;
+        let letters: Vec<char> = code.chars().collect();
+        if letters.len() != expected {
+            return Err(DecodeError::Length {
+                expected,
+                found: letters.len(),
+            });
+        }
+        Ok(Seat {
+            row: self.rows.decode(&letters[..split], 0)?,
+            col: self.cols.decode(&letters[split..], split)?,
+        })
+    }
+
+    pub fn contains(&self, seat: Seat) -> bool {
+        seat.row < self.rows.size() && seat.col < self.cols.size()
+    }
+
+    /// Seats are numbered row by row from the front.
+    pub fn id(&self, seat: Seat) -> usize {
+        seat.row * self.cols.size() + seat.col
+    }
+
+    /// The seat with an id, or `None` if it's beyond the back row.
+    pub fn seat(&self, id: usize) -> Option<Seat> {
+        let seat = Seat {
+            row: id / self.cols.size(),
+            col: id % self.cols.size(),
+        };
+        if seat.row < self.rows.size() {
+            Some(seat)
+        } else {
+            None
+        }
+    }
+
+    /// Counts how many times each seat appears in `seats`, which must all be on the plane.
+    pub fn seat_map(&self, seats: &[Seat]) -> Result<SeatMap, PlaneError> {
+        let mut counts = vec![0; self.rows.size() * self.cols.size()];
+        for seat in seats {
+            let count = Some(*seat)
+                .filter(|seat| self.contains(*seat))
+                .and_then(|seat| counts.get_mut(self.id(seat)))
+                .ok_or(PlaneError::OffPlane(*seat))?;
+            *count += 1;
+        }
+        Ok(SeatMap {
+            plane: *self,
+            counts,
+        })
+    }
+}
+
+/// How many times each seat of a plane was listed, indexed by seat id.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SeatMap {
+    pub plane: Plane,
+    pub counts: Vec<usize>,
+}
+
+impl SeatMap {
+    fn seats_where<F: Fn(usize) -> bool>(&self, keep: F) -> Vec<Seat> {
+        self.counts
+            .iter()
+            .enumerate()
+            .filter(|(_, count)| keep(**count))
+            .filter_map(|(id, _)| self.plane.seat(id))
+            .collect()
+    }
+
+    /// Seats listed more than once.
+    pub fn duplicates(&self) -> Vec<Seat> {
+        self.seats_where(|count| count > 1)
+    }
+
+    /// Empty seats between the first and last listed seat.
+    pub fn gaps(&self) -> Vec<Seat> {
+        let first = self.counts.iter().position(|count| *count > 0);
+        let last = self.counts.iter().rposition(|count| *count > 0);
+        match (first, last) {
+            (Some(first), Some(last)) => (first..=last)
+                .filter(|id| self.counts[*id] == 0)
+                .filter_map(|id| self.plane.seat(id))
+                .collect(),
+            _ => vec![],
+        }
+    }
+
+    /// Empty seats whose ids are one either side of listed seats.
+    pub fn between_taken(&self) -> Vec<Seat> {
+        let taken = |id: Option<usize>| {
+            id.and_then(|id| self.counts.get(id))
+                .is_some_and(|c| *c > 0)
+        };
+        self.gaps()
+            .into_iter()
+            .filter(|seat| {
+                let id = self.plane.id(*seat);
+                taken(id.checked_sub(1)) && taken(Some(id + 1))
+            })
+            .collect()
+    }
+
+    /// Draws a line per row, front first: `#` for a listed seat, `!` for a seat listed more than
+    /// once, `.` for an empty seat and `O` for the seats in `marked`.
+    pub fn render(&self, marked: &[Seat]) -> String {
+        let mut text = String::new();
+        for row in 0..self.plane.rows.size() {
+            for col in 0..self.plane.cols.size() {
+                let seat = Seat { row, col };
+                text.push(match self.counts[self.plane.id(seat)] {
+                    _ if marked.contains(&seat) => 'O',
+                    0 => '.',
+                    1 => '#',
+                    _ => '!',
+                });
+            }
+            text.push('\n');
+        }
+        text
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn other_planes() {
+        // 4 rows picked with up and down, then 4 columns picked with west and east
+        let plane = Plane::new(
+            Axis::new('U', 'D', 2).unwrap(),
+            Axis::new('W', 'E', 2).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(plane.decode("DUEW"), Ok(Seat { row: 2, col: 2 }));
+        assert_eq!(plane.id(Seat { row: 2, col: 2 }), 10);
+        assert_eq!(plane.seat(10), Some(Seat { row: 2, col: 2 }));
+        assert_eq!(plane.seat(16), None);
+        assert_eq!(
+            plane.decode("DUEWW"),
+            Err(DecodeError::Length {
+                expected: 4,
+                found: 5
+            })
+        );
+        assert_eq!(
+            plane.decode("DUEU"),
+            Err(DecodeError::Letter {
+                position: 4,
+                found: 'U',
+                expected: ('W', 'E')
+            })
+        );
+        // letters are counted as characters, not bytes
+        assert_eq!(
+            plane.decode("DUÉW"),
+            Err(DecodeError::Letter {
+                position: 3,
+                found: 'É',
+                expected: ('W', 'E')
+            })
+        );
+    }
+
+    #[test]
+    fn seat_map() {
+        let plane = Plane::new(
+            Axis::new('F', 'B', 2).unwrap(),
+            Axis::new('L', 'R', 1).unwrap(),
+        )
+        .unwrap();
+        let seats: Vec<Seat> = ["FBL", "FBR", "BBL", "FBR", "BFR"]
+            .iter()
+            .map(|code| plane.decode(code).unwrap())
+            .collect();
+        let map = plane.seat_map(&seats).unwrap();
+        assert_eq!(map.duplicates(), vec![Seat { row: 1, col: 1 }]);
+        assert_eq!(map.gaps(), vec![Seat { row: 2, col: 0 }]);
+        assert_eq!(map.between_taken(), vec![Seat { row: 2, col: 0 }]);
+        assert_eq!(map.render(&map.gaps()), "..\n#!\nO#\n#.\n");
+
+        let stray = Seat { row: 1, col: 2 };
+        assert_eq!(plane.seat_map(&[stray]), Err(PlaneError::OffPlane(stray)));
+    }
+
+    #[test]
+    fn bad_planes() {
+        assert_eq!(Axis::new('F', 'F', 7), Err(PlaneError::SameLetter('F')));
+        assert_eq!(
+            Axis::new('F', 'B', 64),
+            Err(PlaneError::TooWide { bits: 64 })
+        );
+        let half = Axis::new('F', 'B', usize::BITS / 2).unwrap();
+        assert_eq!(
+            Plane::new(half, half),
+            Err(PlaneError::TooWide { bits: usize::BITS })
+        );
+    }
+}
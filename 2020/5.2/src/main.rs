@@ -1,96 +1,69 @@
-use std::str::FromStr;
 use std::{env, fs};
 use std::io::prelude::*;
 use std::io::BufReader;
 
-#[derive(Debug, PartialEq)]
-struct Seat {
-    row: i8,
-    col: i8,
-}
-
-impl Seat {
-
-    fn from_file(path: &str) -> anyhow::Result<Vec<Seat>> {
-        let mut seats = Vec::new();
-        let file = fs::File::open(&path)?;
-        let reader = BufReader::new(&file);
-        for line in reader.lines() {
-            let line = line?;
-            seats.push(Seat::from_str(line.trim())?)
-        }
-        Ok(seats)
-    }
-
-    fn id(&self) -> isize {
-        (self.row as isize) * 8 + (self.col as isize)
-    }
-
-    fn from_id(id: isize) -> Self {
-        let row = (id / 8) as i8;
-        let col = (id % 8) as i8;
-        Seat { row, col }
-    }
-}
+mod bsp;
 
+use bsp::{Plane, Seat, SeatMap};
 
-impl FromStr for Seat {
-    type Err = anyhow::Error;
-
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        if s.len() != 10 {
-            return Err(anyhow::anyhow!("Invalid seat string"))
-        }
-        let bin_str = s
-            .replace("R", "1")
-            .replace("B", "1")
-            .replace("L", "0")
-            .replace("F", "0");
-        let row = i8::from_str_radix(&bin_str[0..7], 2)?;
-        let col = i8::from_str_radix(&bin_str[7..10], 2)?;
-        Ok(Seat { row, col })
+fn read_seats(path: &str, plane: &Plane) -> anyhow::Result<Vec<Seat>> {
+    let mut seats = Vec::new();
+    let file = fs::File::open(path)?;
+    let reader = BufReader::new(&file);
+    for (i, line) in reader.lines().enumerate() {
+        let line = line?;
+        let seat = plane.decode(line.trim())
+            .map_err(|err| anyhow::anyhow!("line {}: {}", i + 1, err))?;
+        seats.push(seat)
     }
+    Ok(seats)
 }
 
-
-fn find_seat(seat_list: &[Seat]) -> Option<Seat> {
-    let mut ids: Vec<isize> = seat_list.iter().map(Seat::id).collect();
-    ids.sort();
-    let mut ids = ids.into_iter();
-    let mut p = ids.next().unwrap();
-    for id in ids {
-        let expected_id = p + 1;
-        if id != expected_id {
-            return Some(Seat::from_id(expected_id))
-        }
-        p = id;
-    }
-    None
+/// Finds the empty seat whose neighbouring ids are both taken.
+fn find_seat(map: &SeatMap) -> Option<Seat> {
+    map.between_taken().into_iter().next()
 }
 
 fn main() {
     let path = env::args().nth(1).expect("Input file argument is required");
-    let seats = Seat::from_file(&path).unwrap();
-    let my_seat = find_seat(&seats).expect("could not find seat");
-    println!("{}", my_seat.id())
+    let plane = Plane::airline();
+    let seats = read_seats(&path, &plane).unwrap();
+    let map = plane.seat_map(&seats).unwrap();
+    let my_seat = find_seat(&map).expect("could not find seat");
+    if env::args().skip(2).any(|arg| arg == "--map") {
+        for seat in map.duplicates() {
+            println!("seat {} is listed more than once", plane.id(seat));
+        }
+        print!("{}", map.render(&[my_seat]));
+    }
+    println!("{}", plane.id(my_seat))
 }
 
 #[cfg(test)]
 mod test {
-    use std::str::FromStr;
-    use super::Seat;
+    use super::*;
 
     #[test]
     fn seat_string_translation() {
-        assert_eq!(Seat::from_str("FBFBBFFRLR").unwrap(), Seat { row: 44, col: 5 });
-        assert_eq!(Seat::from_str("FFFBBBFRRR").unwrap(), Seat { row: 14, col: 7 });
-        assert_eq!(Seat::from_str("BBFFBBFRLL").unwrap(), Seat { row: 102, col: 4 });
+        let plane = Plane::airline();
+        assert_eq!(plane.decode("FBFBBFFRLR").unwrap(), Seat { row: 44, col: 5 });
+        assert_eq!(plane.decode("FFFBBBFRRR").unwrap(), Seat { row: 14, col: 7 });
+        assert_eq!(plane.decode("BBFFBBFRLL").unwrap(), Seat { row: 102, col: 4 });
     }
 
     #[test]
     fn seat_id() {
-        assert_eq!(Seat { row: 44, col: 5 }.id(), 357);
-        assert_eq!(Seat { row: 14, col: 7 }.id(), 119);
-        assert_eq!(Seat { row: 102, col: 4 }.id(), 820);
+        let plane = Plane::airline();
+        assert_eq!(plane.id(Seat { row: 44, col: 5 }), 357);
+        assert_eq!(plane.id(Seat { row: 14, col: 7 }), 119);
+        assert_eq!(plane.id(Seat { row: 102, col: 4 }), 820);
+    }
+
+    #[test]
+    fn my_seat() {
+        let plane = Plane::airline();
+        let seats: Vec<Seat> = [356, 358, 359].iter().filter_map(|id| plane.seat(*id)).collect();
+        let map = plane.seat_map(&seats).unwrap();
+        assert_eq!(find_seat(&map), Some(Seat { row: 44, col: 5 }));
     }
 }
@@ -1,36 +1,19 @@
-use std::str::FromStr;
-use std::{env, fs};
-use std::io::prelude::*;
-use std::io::BufReader;
+use std::env;
 use std::collections::HashSet;
+use records::Groups;
 
 type Group = HashSet<char>;
 
 
 fn read_groups(path: &str) -> anyhow::Result<Vec<Group>> {
-    let file = fs::File::open(path)?;
-    let reader = BufReader::new(&file);
-    let mut groups = Vec::new();
-    let mut group = Vec::new();
-    for line in reader.lines() {
-        let line = line?.trim().to_string();
-        if &line == "" {
-            groups.push(group);
-            group = Vec::new();
-        } else {
-            group.push(HashSet::from_iter(line.chars()));
-        }
-    }
-    if group.len() > 0 {
-        groups.push(group);
-    }
-    let groups = groups.into_iter().map(|people| {
-        people.into_iter().reduce(|mut accum, person| {
+    let groups = Groups::open(path)?.parse(|group| {
+        let people = group.lines.iter().map(|line| HashSet::from_iter(line.trim().chars()));
+        Ok(people.reduce(|mut accum: Group, person| {
             accum.retain(|i| person.contains(i));
             accum
-        }).unwrap()
+        }).unwrap())
     });
-    Ok(groups.collect())
+    Ok(groups.collect::<Result<_, _>>()?)
 }
 
 fn main() -> anyhow::Result<()> {
@@ -0,0 +1,229 @@
+//! Reads inputs made of records separated by blank lines.
+//!
+//! Several puzzles list records, such as passports, answer groups, bingo boards or an elf's
+//! snacks, over one or more lines with a blank line between records. `Groups` streams those
+//! records from any reader, one `Group` of lines at a time, and `Groups::parse` turns each group
+//! into a value. Errors say which group and line they came from.
+//!
+//! Lines have `\r\n` or `\n` endings and trailing whitespace removed. A line holding only
+//! whitespace counts as blank, several blank lines in a row separate just one pair of groups and
+//! the last group doesn't need a blank line or newline after it.
+use std::error::Error as StdError;
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader};
+use std::path::Path;
+
+type BoxError = Box<dyn StdError + Send + Sync>;
+
+/// A record's lines.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Group {
+    /// The group's position in the input, starting from 1.
+    pub number: usize,
+    /// The line number of the group's first line, starting from 1.
+    pub first_line: usize,
+    pub lines: Vec<String>,
+}
+
+impl Group {
+    /// The lines with their line numbers.
+    pub fn numbered_lines(&self) -> impl Iterator<Item = (usize, &str)> {
+        (self.first_line..).zip(self.lines.iter().map(String::as_str))
+    }
+
+    /// An error about the whole group, placed at its first line.
+    pub fn error<E: Into<BoxError>>(&self, err: E) -> Error {
+        Error::parse(self.number, self.first_line, err.into())
+    }
+
+    /// Parses every line with `parse`, reporting the line that failed.
+    pub fn parse_lines<T, E, F>(&self, mut parse: F) -> Result<Vec<T>, Error>
+    where
+        E: Into<BoxError>,
+        F: FnMut(&str) -> Result<T, E>,
+    {
+        self.numbered_lines()
+            .map(|(line, text)| {
+                parse(text).map_err(|err| Error::parse(self.number, line, err.into()))
+            })
+            .collect()
+    }
+}
+
+#[derive(Debug)]
+pub enum ErrorKind {
+    Io(io::Error),
+    Parse(BoxError),
+}
+
+/// An error reading or parsing a group.
+#[derive(Debug)]
+pub struct Error {
+    /// The group being read, starting from 1.
+    pub group: usize,
+    /// The line being read, or the first line of the group if the whole group failed to parse.
+    pub line: usize,
+    pub kind: ErrorKind,
+}
+
+impl Error {
+    pub fn parse(group: usize, line: usize, err: BoxError) -> Self {
+        Error {
+            group,
+            line,
+            kind: ErrorKind::Parse(err),
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match &self.kind {
+            ErrorKind::Io(err) => write!(f, "group {}, line {}: {}", self.group, self.line, err),
+            ErrorKind::Parse(err) => write!(
+                f,
+                "group {}, line {}: parse error: {}",
+                self.group, self.line, err
+            ),
+        }
+    }
+}
+
+impl StdError for Error {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        match &self.kind {
+            ErrorKind::Io(err) => Some(err),
+            ErrorKind::Parse(err) => Some(err.as_ref()),
+        }
+    }
+}
+
+/// An iterator over the groups of a reader.
+pub struct Groups<R> {
+    lines: io::Lines<R>,
+    line: usize,
+    group: usize,
+}
+
+impl<R: BufRead> Groups<R> {
+    pub fn new(reader: R) -> Self {
+        Groups {
+            lines: reader.lines(),
+            line: 0,
+            group: 0,
+        }
+    }
+
+    /// Parses each group with `parse`, which reports problems with `Group::error` or
+    /// `Group::parse_lines`.
+    pub fn parse<T, F>(self, mut parse: F) -> impl Iterator<Item = Result<T, Error>>
+    where
+        F: FnMut(&Group) -> Result<T, Error>,
+    {
+        self.map(move |group| parse(&group?))
+    }
+}
+
+impl Groups<BufReader<File>> {
+    pub fn open<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        Ok(Groups::new(BufReader::new(File::open(path)?)))
+    }
+}
+
+impl<'a> Groups<&'a [u8]> {
+    pub fn from_text(text: &'a str) -> Self {
+        Groups::new(text.as_bytes())
+    }
+}
+
+impl<R: BufRead> Iterator for Groups<R> {
+    type Item = Result<Group, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut lines = Vec::new();
+        let mut first_line = self.line + 1;
+        for line in &mut self.lines {
+            self.line += 1;
+            let line = match line {
+                Ok(line) => line,
+                Err(err) => {
+                    return Some(Err(Error {
+                        group: self.group + 1,
+                        line: self.line,
+                        kind: ErrorKind::Io(err),
+                    }))
+                }
+            };
+            let line = line.trim_end();
+            if !line.is_empty() {
+                lines.push(line.to_string());
+            } else if lines.is_empty() {
+                first_line = self.line + 1;
+            } else {
+                break;
+            }
+        }
+        if lines.is_empty() {
+            return None;
+        }
+        self.group += 1;
+        Some(Ok(Group {
+            number: self.group,
+            first_line,
+            lines,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines(groups: &[Group]) -> Vec<Vec<&str>> {
+        groups
+            .iter()
+            .map(|group| group.lines.iter().map(String::as_str).collect())
+            .collect()
+    }
+
+    #[test]
+    fn separators() {
+        let groups: Vec<Group> = Groups::from_text("\na\nb\n\n\n \nc  \r\nd\r\n\r\ne")
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(
+            lines(&groups),
+            vec![vec!["a", "b"], vec!["c", "d"], vec!["e"]]
+        );
+        let starts: Vec<(usize, usize)> = groups.iter().map(|g| (g.number, g.first_line)).collect();
+        assert_eq!(starts, vec![(1, 2), (2, 7), (3, 10)]);
+
+        let trailing: Vec<Group> = Groups::from_text("a\n\nb\n\n")
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(lines(&trailing), vec![vec!["a"], vec!["b"]]);
+        assert_eq!(Groups::from_text("").count(), 0);
+        assert_eq!(Groups::from_text("\n\n").count(), 0);
+    }
+
+    #[test]
+    fn parse_errors() {
+        let parse = |group: &Group| group.parse_lines(|line| line.parse::<u32>());
+        let sums: Vec<u32> = Groups::from_text("1\n2\n\n3\n")
+            .parse(parse)
+            .map(|nums| nums.unwrap().iter().sum())
+            .collect();
+        assert_eq!(sums, vec![3, 3]);
+
+        let err = Groups::from_text("1\n2\n\n3\nx\n")
+            .parse(parse)
+            .find_map(Result::err)
+            .unwrap();
+        assert_eq!((err.group, err.line), (2, 5));
+        assert_eq!(
+            err.to_string(),
+            "group 2, line 5: parse error: invalid digit found in string"
+        );
+    }
+}
@@ -1,6 +1,5 @@
-use std::{fs, env};
-use std::io::BufReader;
-use std::io::prelude::*;
+use std::env;
+use records::Groups;
 use std::collections::HashSet;
 
 type Cell = u8;
@@ -15,27 +14,25 @@ struct Game {
 
 impl Game {
     fn from_file(path: &str) -> anyhow::Result<Self> {
-        let file = fs::File::open(path)?;
-        let reader = BufReader::new(&file);
-        let mut lines = reader.lines().map(|result| result.map(|l| l.trim().to_string()));
-        let calls: Vec<Cell> = lines.next().unwrap()?.split(',').map(|i| Cell::from_str_radix(i, 10).unwrap()).collect();
-        // discard empty line after call line
-        lines.next();
-        let mut board = Vec::new();
-        let mut boards = Vec::new();
-        for line in lines {
-            let line = line?;
-            if line == "" {
-                boards.push(board);
-                board = Vec::new();
-                continue
+        let mut groups = Groups::open(path)?;
+        let first = groups.next().ok_or_else(|| anyhow::anyhow!("no calls in {}", path))??;
+        let line = match &first.lines[..] {
+            [line] => line,
+            lines => {
+                let message = format!("expected one line of calls, found {}", lines.len());
+                return Err(first.error(message).into());
             }
-            let row: Vec<Cell> = line.split_ascii_whitespace().map(|i| Cell::from_str_radix(i, 10).unwrap()).collect();
-            board.push(row)
-        }
-        if board.len() > 0 {
-            boards.push(board);
-        }
+        };
+        let calls: Vec<Cell> = line.trim().split(',')
+            .map(|i| i.parse())
+            .collect::<Result<_, _>>()
+            .map_err(|err| first.error(err))?;
+        let boards = groups.parse(|group| {
+            group.parse_lines(|line| {
+                line.split_ascii_whitespace().map(|i| i.parse()).collect::<Result<Vec<Cell>, _>>()
+            })
+        });
+        let boards = boards.collect::<Result<_, _>>()?;
         Ok(Game { calls, boards, call_pointer: 0 })
     }
 
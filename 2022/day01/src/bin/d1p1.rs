@@ -1,22 +1,13 @@
 use std::cmp::Reverse;
-use std::fs::File;
-use std::io::prelude::*;
-use std::io::BufReader;
+
+use records::Groups;
 
 fn main() -> anyhow::Result<()> {
-    let f = File::open("input.txt")?;
-    let reader = BufReader::new(f);
-    let mut elves: Vec<usize> = Vec::new();
-    let mut elf_cals = 0usize;
-    for line in reader.lines() {
-        match line?.trim() {
-            "" => {
-                elves.push(elf_cals);
-                elf_cals = 0;
-            }
-            int_str => elf_cals += int_str.parse::<usize>()?,
-        }
-    }
+    let elves = Groups::open("input.txt")?
+        .parse(|elf| elf.parse_lines(|line| line.trim().parse::<usize>()));
+    let mut elves: Vec<usize> = elves
+        .map(|snacks| snacks.map(|snacks| snacks.iter().sum()))
+        .collect::<Result<_, _>>()?;
     elves.sort_by_key(|&num| Reverse(num));
     println!("Answer: {}", elves[0]);
     Ok(())
@@ -1,19 +1,15 @@
 use std::cmp::Reverse;
 use std::fs;
 
+use records::Groups;
+
 fn main() -> anyhow::Result<()> {
-    let content = String::from_utf8(fs::read("input.txt")?)?;
-    let mut elves: Vec<usize> = Vec::new();
-    let mut elf_cals = 0usize;
-    for line in content.lines() {
-        match line.trim() {
-            "" => {
-                elves.push(elf_cals);
-                elf_cals = 0;
-            }
-            int_str => elf_cals += int_str.parse::<usize>()?,
-        }
-    }
+    let content = fs::read_to_string("input.txt")?;
+    let elves = Groups::from_text(&content)
+        .parse(|elf| elf.parse_lines(|line| line.trim().parse::<usize>()));
+    let mut elves: Vec<usize> = elves
+        .map(|snacks| snacks.map(|snacks| snacks.iter().sum()))
+        .collect::<Result<_, _>>()?;
     elves.sort_by_key(|&num| Reverse(num));
     let top3: usize = elves[..3].iter().sum();
     println!("Answer: {}", top3);